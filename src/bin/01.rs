@@ -1,77 +1,23 @@
-use std::collections::HashMap;
-
-use itertools::Itertools;
-use lazy_static::lazy_static;
-use regex::Regex;
+use advent_of_code::lists::{parse_columns, similarity_score, sorted_distance};
 
 advent_of_code::solution!(1);
 
-lazy_static! {
-    static ref regex_pattern: Regex = Regex::new(r"(\d+)   (\d+)").unwrap();
-}
-
-struct Line {
-    left: i64,
-    right: i64,
-}
-
-impl Line {
-    fn parse(line: &str) -> Option<Line> {
-        let captures = regex_pattern.captures(line)?;
-
-        Some(Line {
-            left: captures[1].parse().ok()?,
-            right: captures[2].parse().ok()?,
-        })
-    }
+// The two lists, side by side
+fn parse(input: &str) -> Option<(Vec<i64>, Vec<i64>)> {
+    let mut columns = parse_columns(input, 2)?;
+    let right = columns.pop()?;
+    let left = columns.pop()?;
+    Some((left, right))
 }
 
 pub fn part_one(input: &str) -> Option<i64> {
-    let tuples = input.lines().map(Line::parse).collect::<Option<Vec<_>>>()?;
-
-    let left: Vec<i64> = tuples.iter().map(|l| l.left).collect();
-    let right: Vec<i64> = tuples.iter().map(|l| l.right).collect();
-
-    let left_sorted: Vec<i64> = left.into_iter().sorted().collect();
-    let right_sorted: Vec<i64> = right.into_iter().sorted().collect();
-
-    let total_diff = left_sorted
-        .into_iter()
-        .zip(right_sorted.into_iter())
-        .map(|(l, r)| (l-r).abs())
-        .sum();
-
-    Some(total_diff)
-}
-
-fn counts(numbers: Vec<i64>) -> HashMap<i64, i64> {
-    let mut hashmap: HashMap<i64, i64> = HashMap::new();
-
-    for number in &numbers {
-        hashmap.entry(*number).and_modify(|entry| *entry += 1).or_insert(1);
-    }
-
-    hashmap
+    let (left, right) = parse(input)?;
+    sorted_distance(&left, &right).try_into().ok()
 }
 
 pub fn part_two(input: &str) -> Option<i64> {
-    let tuples = input.lines().map(Line::parse).collect::<Option<Vec<_>>>()?;
-
-    let right: Vec<i64> = tuples.iter().map(|l| l.right).collect();
-    let right_counts = counts(right);
-
-    let left: Vec<i64> = tuples.iter().map(|l| l.left).collect();
-
-    let similarity: i64 = left
-        .into_iter()
-        .map(|num| {
-            match right_counts.get(&num) {
-                Some(&i) => num * i,
-                None => 0
-            }
-        }).sum();
-    
-    Some(similarity)
+    let (left, right) = parse(input)?;
+    Some(similarity_score(&left, &right))
 }
 
 #[cfg(test)]
@@ -79,13 +25,10 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_counts() {
-        let v: Vec<i64> = vec![3, 4, 3];
-        let counts = counts(v);
-
-        assert_eq!(Some(&2), counts.get(&3));
-        assert_eq!(Some(&1), counts.get(&4));
-        assert_eq!(None, counts.get(&1));
+    fn test_parse() {
+        let result = parse("3   4\n4 3\n");
+        assert_eq!(result, Some((vec![3, 4], vec![4, 3])));
+        assert_eq!(parse("3   4   5"), None);
     }
 
     #[test]
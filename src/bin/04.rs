@@ -1,178 +1,241 @@
-advent_of_code::solution!(4);
+use itertools::Itertools;
 
-fn num_cols(input: &str) -> usize {
-    let first_line = input.lines().next().unwrap();
-    first_line.len() 
-}
+advent_of_code::solution!(4);
 
-fn num_rows(input: &str) -> usize {
-    let lines: Vec<&str> = input.lines().collect();
-    lines.len()
+// Coordinates in format (row, col)
+type Coord = (usize, usize);
+type Offset = (isize, isize);
+
+const ALL_DIRECTIONS: [Offset; 8] = [
+    (-1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+];
+
+// Letters at offsets from an anchor, which is where a match is reported
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct Pattern {
+    cells: Vec<(Offset, u8)>,
 }
 
-fn vertical_candidates(input: &str) -> Vec<String> {
-    let rows = num_rows(input);
-    let cols = num_cols(input);
-
-    let mut candidates: Vec<String> = vec![];
-
-    let lines: Vec<&str> = input.lines().collect();
-
-    for i_col in 0..cols {
-        for i_start_row in 0..rows-3 {
-            let mut candidate = String::new();
-            for i_row in i_start_row..i_start_row+4 {
-                let character = lines[i_row].chars().nth(i_col).unwrap();
-                candidate.push(character);
-            }
-            candidates.push(candidate)
-        }
+impl Pattern {
+    // A word written in the direction, anchored at its first letter
+    fn word(word: &str, direction: Offset) -> Pattern {
+        let cells = word
+            .bytes()
+            .enumerate()
+            .map(|(i, letter)| {
+                let i = i as isize;
+                ((i * direction.0, i * direction.1), letter)
+            })
+            .collect();
+        Pattern { cells }
     }
 
-    candidates
-}
-
-fn horizontal_candidates(input: &str) -> Vec<&str> {
-    let rows = num_rows(input);
-    let cols = num_cols(input);
-
-    let mut candidates: Vec<&str> = vec![];
-
-    let lines: Vec<&str> = input.lines().collect();
-
-    for i_row in 0..rows {
-        let line = lines[i_row];
-        for i_col in 0..(cols-3) {
-            // let candidate = line[i_col..i_col+4].to_string();
-            let candidate = &line[i_col..i_col+4];
-            candidates.push(candidate)
-        }
+    // A pattern drawn as lines of text, where '.' matches any letter. Anchored at the top left.
+    fn parse(shape: &str) -> Pattern {
+        let cells = shape
+            .lines()
+            .enumerate()
+            .flat_map(|(row, line)| {
+                line.bytes()
+                    .enumerate()
+                    .filter(|(_, letter)| *letter != b'.')
+                    .map(move |(col, letter)| ((row as isize, col as isize), letter))
+            })
+            .collect();
+        Pattern { cells }.normalized()
     }
 
-    candidates
-}
-
-fn diagonal_candidates_down_right(input: &str) -> Vec<String> {
-    let rows = num_rows(input);
-    let cols = num_cols(input);
-
-    let mut candidates: Vec<String> = vec![];
-
-    let lines: Vec<&str> = input.lines().collect();
+    // The same pattern anchored at the top left corner of its bounding box, with the cells sorted
+    fn normalized(&self) -> Pattern {
+        let min_row = self.cells.iter().map(|((row, _), _)| *row).min();
+        let min_col = self.cells.iter().map(|((_, col), _)| *col).min();
+        let mut cells = self
+            .cells
+            .iter()
+            .map(|((row, col), letter)| {
+                (
+                    (row - min_row.unwrap_or(0), col - min_col.unwrap_or(0)),
+                    *letter,
+                )
+            })
+            .collect_vec();
+        cells.sort();
+        Pattern { cells }
+    }
 
-    for i_col in 0..cols-3 {
-        for i_start_row in 0..rows-3 {
-            let mut candidate = String::new();
-            for j in 0..4 {
-                let character = lines[i_start_row + j].chars().nth(i_col + j).unwrap();
-                candidate.push(character);
-            }
-            candidates.push(candidate)
-        }
+    // A quarter turn clockwise, anchored at the top left
+    fn rotated(&self) -> Pattern {
+        let cells = self
+            .cells
+            .iter()
+            .map(|((row, col), letter)| ((*col, -row), *letter))
+            .collect();
+        Pattern { cells }.normalized()
     }
 
-    candidates
+    // The pattern in all four orientations, without duplicates for symmetric patterns
+    fn rotations(&self) -> Vec<Pattern> {
+        std::iter::successors(Some(self.normalized()), |pattern| Some(pattern.rotated()))
+            .take(4)
+            .unique()
+            .collect()
+    }
 }
 
-fn diagonal_candidates_down_left(input: &str) -> Vec<String> {
-    let rows = num_rows(input);
-    let cols = num_cols(input);
-
-    let mut candidates: Vec<String> = vec![];
-
-    let lines: Vec<&str> = input.lines().collect();
+struct WordSearch<'a> {
+    lines: Vec<&'a [u8]>,
+}
 
-    for i_col in 3..cols {
-        for i_start_row in 0..rows-3 {
-            let mut candidate = String::new();
-            for j in 0..4 {
-                let character = lines[i_start_row + j].chars().nth(i_col - j).unwrap();
-                candidate.push(character);
-            }
-            candidates.push(candidate)
+impl<'a> WordSearch<'a> {
+    fn new(input: &'a str) -> WordSearch<'a> {
+        WordSearch {
+            lines: input.lines().map(str::as_bytes).collect(),
         }
     }
 
-    candidates
-}
-
-fn x_candidates(input: &str) -> Vec<(String, String)> {
-    let rows = num_rows(input);
-    let cols = num_cols(input);
-
-    let mut candidates: Vec<(String, String)> = vec![];
-
-    let lines: Vec<&str> = input.lines().collect();
-
-    for i_col in 0..cols-2 {
-        for i_start_row in 0..rows-2 {
-            let mut candidate1 = String::new();
-            let mut candidate2 = String::new();
-            for j in 0..3 {
-                let character1 = lines[i_start_row + j].chars().nth(i_col + j).unwrap();
-                let character2 = lines[i_start_row + j].chars().nth(i_col + 2 - j).unwrap();
-                candidate1.push(character1);
-                candidate2.push(character2);
-            }
-            let tup = (candidate1, candidate2);
-            candidates.push(tup);
-        }
+    fn get(&self, anchor: Coord, offset: Offset) -> Option<u8> {
+        let row = anchor.0.checked_add_signed(offset.0)?;
+        let col = anchor.1.checked_add_signed(offset.1)?;
+        self.lines.get(row)?.get(col).copied()
     }
 
-    candidates
-}
+    fn matches_at(&self, pattern: &Pattern, anchor: Coord) -> bool {
+        pattern
+            .cells
+            .iter()
+            .all(|(offset, letter)| self.get(anchor, *offset) == Some(*letter))
+    }
 
-pub fn is_xmas(s: &str) -> bool {
-    s == "XMAS" || s == "SAMX"
-}
+    // Every anchor where the pattern matches, in row-major order
+    fn find(&self, pattern: &Pattern) -> Vec<Coord> {
+        self.lines
+            .iter()
+            .enumerate()
+            .flat_map(|(row, line)| (0..line.len()).map(move |col| (row, col)))
+            .filter(|anchor| self.matches_at(pattern, *anchor))
+            .collect()
+    }
 
-pub fn is_mas(s: &str) -> bool {
-    s == "MAS" || s == "SAM"
+    // Every start of the word in any of the directions, with the direction it's written in
+    fn find_word(&self, word: &str, directions: &[Offset]) -> Vec<(Coord, Offset)> {
+        directions
+            .iter()
+            .flat_map(|direction| {
+                let pattern = Pattern::word(word, *direction);
+                self.find(&pattern)
+                    .into_iter()
+                    .map(move |start| (start, *direction))
+            })
+            .collect()
+    }
 }
 
 pub fn part_one(input: &str) -> Option<u32> {
-    let num_horizontal = horizontal_candidates(input).into_iter().filter(|candidate| is_xmas(candidate)).count();
-    let num_vertical = vertical_candidates(input).into_iter().filter(|candidate| is_xmas(candidate)).count();
-    let num_diagonal_1 = diagonal_candidates_down_right(input).into_iter().filter(|candidate| is_xmas(candidate)).count();
-    let num_diagonal_2 = diagonal_candidates_down_left(input).into_iter().filter(|candidate| is_xmas(candidate)).count();
-
-    let sum: Option<u32> = (num_horizontal + num_vertical + num_diagonal_1 + num_diagonal_2).try_into().ok();
-    sum
+    let search = WordSearch::new(input);
+    let count = search.find_word("XMAS", &ALL_DIRECTIONS).len();
+    count.try_into().ok()
 }
 
 pub fn part_two(input: &str) -> Option<u32> {
-    let sum_cross = x_candidates(input).into_iter().filter(|(first, second)| is_mas(first) && is_mas(second)).count();
-    let sum: Option<u32> = sum_cross.try_into().ok();
-    sum
+    let search = WordSearch::new(input);
+    let x_mas = Pattern::parse("M.S\n.A.\nM.S");
+    let count: usize = x_mas
+        .rotations()
+        .iter()
+        .map(|pattern| search.find(pattern).len())
+        .sum();
+    count.try_into().ok()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    const EAST: Offset = (0, 1);
+    const SOUTH: Offset = (1, 0);
+    const SOUTH_EAST: Offset = (1, 1);
+    const SOUTH_WEST: Offset = (1, -1);
+
     #[test]
-    fn test_horizontal_candidates() {
-        let result = horizontal_candidates("ASDFG");
-        assert_eq!(result, vec!["ASDF", "SDFG"]);
+    fn test_horizontal() {
+        let search = WordSearch::new("ASDFG");
+        assert_eq!(search.find_word("SDFG", &[EAST]), vec![((0, 1), EAST)]);
+        assert_eq!(
+            search.find_word("FDS", &ALL_DIRECTIONS),
+            vec![((0, 3), (0, -1))]
+        );
     }
 
+    #[test]
+    fn test_vertical() {
+        let search = WordSearch::new("A\nS\nD\nF\nG");
+        assert_eq!(search.find_word("ASDF", &[SOUTH]), vec![((0, 0), SOUTH)]);
+        assert_eq!(
+            search.find_word("GF", &ALL_DIRECTIONS),
+            vec![((4, 0), (-1, 0))]
+        );
+    }
+
+    #[test]
+    fn test_diagonal_down_right() {
+        let search = WordSearch::new("QWERT\nASDFG\nZXCVB\nYUIOP\nHJKNM");
+        assert_eq!(
+            search.find_word("QSCO", &[SOUTH_EAST]),
+            vec![((0, 0), SOUTH_EAST)]
+        );
+        assert_eq!(
+            search.find_word("SCOM", &[SOUTH_EAST]),
+            vec![((1, 1), SOUTH_EAST)]
+        );
+        assert_eq!(
+            search.find_word("MOCS", &ALL_DIRECTIONS),
+            vec![((4, 4), (-1, -1))]
+        );
+    }
 
     #[test]
-    fn test_vertical_candidates() {
-        let result = vertical_candidates("A\nS\nD\nF\nG");
-        assert_eq!(result, vec!["ASDF", "SDFG"]);
+    fn test_diagonal_down_left() {
+        let search = WordSearch::new("QWERT\nASDFG\nZXCVB\nYUIOP\nHJKNM");
+        assert_eq!(
+            search.find_word("RDXY", &[SOUTH_WEST]),
+            vec![((0, 3), SOUTH_WEST)]
+        );
+        assert_eq!(
+            search.find_word("GVIJ", &ALL_DIRECTIONS),
+            vec![((1, 4), SOUTH_WEST)]
+        );
+        assert_eq!(
+            search.find_word("JIVG", &ALL_DIRECTIONS),
+            vec![((4, 1), (-1, 1))]
+        );
     }
 
     #[test]
-    fn test_diagonal_down_right_candidates() {
-        let result = diagonal_candidates_down_right("QWERT\nASDFG\nZXCVB\nYUIOP\nHJKNM");
-        assert_eq!(result, vec!["QSCO", "AXIN", "WDVP", "SCOM"]);
+    fn test_pattern_rotations() {
+        let x_mas = Pattern::parse("M.S\n.A.\nM.S");
+        let rotations = x_mas.rotations();
+        assert_eq!(rotations.len(), 4);
+        assert!(rotations.contains(&Pattern::parse("M.M\n.A.\nS.S")));
+        assert!(rotations.contains(&Pattern::parse("S.M\n.A.\nS.M")));
+
+        // Symmetric patterns only have the distinct orientations
+        assert_eq!(Pattern::parse("A.A\n...\nA.A").rotations().len(), 1);
+        assert_eq!(Pattern::word("AA", EAST).rotations().len(), 2);
     }
 
     #[test]
-    fn test_diagonal_down_left_candidates() {
-        let result = diagonal_candidates_down_left("QWERT\nASDFG\nZXCVB\nYUIOP\nHJKNM");
-        assert_eq!(result, vec!["RDXY", "FCUH", "TFCU", "GVIJ"]);
+    fn test_find_pattern() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        let search = WordSearch::new(&input);
+        let found = search.find(&Pattern::parse("M.S\n.A.\nM.S"));
+        assert_eq!(found, vec![(0, 1), (2, 1)]);
     }
 
     #[test]
@@ -1,6 +1,5 @@
 advent_of_code::solution!(5);
-use std::{collections::{HashMap, HashSet}, cmp::Ordering};
-
+use advent_of_code::ordering::{OrderError, Precedence};
 use lazy_static::lazy_static;
 use regex::Regex;
 
@@ -48,20 +47,6 @@ impl Update {
         Some(Update { pages })
     }
 
-    fn is_valid(&self, rules: &HashSet<(u32, u32)>) -> bool {
-        for i in 0..self.pages.len() - 1 {
-            let first = self.pages[i];
-            for j in i + 1..self.pages.len() {
-                let second = self.pages[j];
-                if !rules.contains(&(first, second)) {
-                    return false;
-                }
-            }
-        }
-
-        return true;
-    }
-
     fn middle(&self) -> u32 {
         let mid_index = self.pages.len() / 2;
         self.pages[mid_index]
@@ -84,60 +69,41 @@ impl Problem {
 
         Some(Problem { rules, updates })
     }
+
+    fn precedence(&self) -> Precedence<u32> {
+        Precedence::from_rules(self.rules.iter().map(|rule| (rule.first, rule.after)))
+    }
+}
+
+// The update with its pages in the only order the rules allow
+fn reorder(precedence: &Precedence<u32>, update: &Update) -> Result<Update, OrderError<u32>> {
+    let pages = precedence.total_order(&update.pages)?;
+    Ok(Update { pages })
 }
 
 pub fn part_one(input: &str) -> Option<u32> {
     let problem = Problem::parse(input)?;
+    let precedence = problem.precedence();
 
-    let rules: HashSet<(u32, u32)> = problem
-        .rules
-        .iter()
-        .map(|rule| (rule.first, rule.after))
-        .collect();
-
-    let valid_middle = problem
+    let sum = problem
         .updates
         .iter()
-        .filter(|update| update.is_valid(&rules))
+        .filter(|update| precedence.is_ordered(&update.pages))
         .map(|update| update.middle())
-        .collect::<Vec<_>>();
-
-    let sum: u32 = valid_middle.into_iter().sum();
+        .sum();
     Some(sum)
 }
 
 pub fn part_two(input: &str) -> Option<u32> {
     let problem = Problem::parse(input)?;
+    let precedence = problem.precedence();
 
-    let rules: HashSet<(u32, u32)> = problem
-        .rules
-        .iter()
-        .map(|rule| (rule.first, rule.after))
-        .collect();
-
-    let invalid_updates = problem
+    problem
         .updates
         .iter()
-        .filter(|update| !update.is_valid(&rules))
-        .collect::<Vec<_>>();
-
-    let sorted_invalid_middle = invalid_updates
-        .iter()
-        .map(|update| {
-            let mut pages = update.pages.clone();
-            pages.sort_by(|a, b|  if !rules.contains(&(*a,*b)) {
-                return Ordering::Less
-            } else {
-                return Ordering::Greater
-            }
-            );
-            Update{ pages }
-        })
-        .map(|update| update.middle())
-        .collect::<Vec<_>>();
-
-    let sum: u32 = sorted_invalid_middle.into_iter().sum();
-    Some(sum)
+        .filter(|update| !precedence.is_ordered(&update.pages))
+        .map(|update| Some(reorder(&precedence, update).ok()?.middle()))
+        .sum()
 }
 
 #[cfg(test)]
@@ -187,7 +153,6 @@ mod tests {
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        // assert_eq!(result, None);
         assert_eq!(result, Some(143));
     }
 
@@ -196,4 +161,34 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(123));
     }
+
+    #[test]
+    fn test_reorder() {
+        let input = advent_of_code::template::read_file("examples", DAY);
+        let problem = Problem::parse(&input).unwrap();
+        let precedence = problem.precedence();
+        let reordered = problem
+            .updates
+            .iter()
+            .map(|update| reorder(&precedence, update).unwrap().pages)
+            .collect::<Vec<_>>();
+        assert_eq!(reordered[3], vec![97, 75, 47, 61, 53]);
+        assert_eq!(reordered[4], vec![61, 29, 13]);
+        assert_eq!(reordered[5], vec![97, 75, 47, 29, 13]);
+    }
+
+    #[test]
+    fn test_reorder_without_total_order() {
+        let problem = Problem::parse("1|2\n2|3\n3|1\n1|4\n\n1,2,3\n4,2").unwrap();
+        let precedence = problem.precedence();
+        assert_eq!(
+            reorder(&precedence, &problem.updates[0]),
+            Err(OrderError::Cycle(vec![1, 2, 3]))
+        );
+        assert_eq!(
+            reorder(&precedence, &problem.updates[1]),
+            Err(OrderError::Ambiguous(4, 2))
+        );
+        assert_eq!(part_two("1|2\n2|3\n3|1\n\n3,2,1"), None);
+    }
 }
@@ -1,5 +1,6 @@
+use advent_of_code::cycle;
 use itertools::Itertools;
-use std::collections::{HashMap, HashSet};
+use rayon::prelude::*;
 
 advent_of_code::solution!(6);
 
@@ -9,7 +10,7 @@ struct BoardSize {
     cols: usize,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 enum Direction {
     North,
     East,
@@ -17,6 +18,13 @@ enum Direction {
     West,
 }
 
+const ALL_DIRECTIONS: [Direction; 4] = [
+    Direction::North,
+    Direction::East,
+    Direction::South,
+    Direction::West,
+];
+
 impl Direction {
     fn turn_right(&self) -> Direction {
         match self {
@@ -26,6 +34,15 @@ impl Direction {
             Direction::West => Self::North,
         }
     }
+
+    fn index(&self) -> usize {
+        match self {
+            Direction::North => 0,
+            Direction::East => 1,
+            Direction::South => 2,
+            Direction::West => 3,
+        }
+    }
 }
 
 type Coord = (usize, usize);
@@ -63,336 +80,185 @@ fn pos_in_direction(pos: Coord, direction: &Direction, size: &BoardSize) -> Opti
     }
 }
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
-enum Tile {
-    Unvisited,
-    Obstruction,
-    Guard(Direction),
-    VisitedPart1,            // Direction is where the player left the last time
-    VisitedPart2(Direction), // Direction is where the player left the last time
+// Number of steps from `from` to `to`, if `to` is straight ahead in the direction
+fn steps_ahead(from: Coord, to: Coord, direction: &Direction) -> Option<usize> {
+    match direction {
+        Direction::North if from.1 == to.1 && to.0 < from.0 => Some(from.0 - to.0),
+        Direction::South if from.1 == to.1 && to.0 > from.0 => Some(to.0 - from.0),
+        Direction::West if from.0 == to.0 && to.1 < from.1 => Some(from.1 - to.1),
+        Direction::East if from.0 == to.0 && to.1 > from.1 => Some(to.1 - from.1),
+        _ => None,
+    }
 }
 
-impl Tile {
-    fn parse(char: char) -> Option<Tile> {
-        match char {
-            '.' => Some(Self::Unvisited),
-            '#' => Some(Self::Obstruction),
-            '^' => Some(Self::Guard(Direction::North)),
-            '>' => Some(Self::Guard(Direction::East)),
-            'v' => Some(Self::Guard(Direction::South)),
-            '<' => Some(Self::Guard(Direction::West)),
-            _ => None,
-        }
+// Moves n steps in the direction, assuming that stays on the board
+fn pos_after_steps(pos: Coord, direction: &Direction, n: usize) -> Coord {
+    match direction {
+        Direction::North => (pos.0 - n, pos.1),
+        Direction::South => (pos.0 + n, pos.1),
+        Direction::West => (pos.0, pos.1 - n),
+        Direction::East => (pos.0, pos.1 + n),
     }
 }
 
-#[derive(Clone)]
+// Where the guard is and which way it faces
+type Pose = (Coord, Direction);
+
 struct Board {
-    tiles: HashMap<Coord, Tile>,
+    obstructions: Vec<bool>,
     size: BoardSize,
+    guard: Pose,
 }
 
 impl Board {
     fn parse(s: &str) -> Option<Board> {
-        let rows = s.lines().collect_vec().len();
-        let cols = s.lines().nth(0).map(|x| x.len())?;
-
-        let tiles = s
-            .lines()
-            .enumerate()
-            .flat_map(|(row, line)| {
-                line.char_indices().map(move |(col, char)| {
-                    let pos: (usize, usize) = (row, col);
-                    if let Some(tile) = Tile::parse(char) {
-                        Some((pos, tile))
-                    } else {
-                        None
-                    }
-                })
-            })
-            .collect::<Option<HashMap<(usize, usize), Tile>>>()?;
+        let rows = s.lines().count();
+        let cols = s.lines().next().map(|x| x.len())?;
+
+        let mut obstructions = vec![false; rows * cols];
+        let mut guard = None;
+        for (row, line) in s.lines().enumerate() {
+            if line.len() != cols {
+                return None;
+            }
+            for (col, char) in line.char_indices() {
+                match char {
+                    '.' => {}
+                    '#' => obstructions[row * cols + col] = true,
+                    '^' => guard = Some(((row, col), Direction::North)),
+                    '>' => guard = Some(((row, col), Direction::East)),
+                    'v' => guard = Some(((row, col), Direction::South)),
+                    '<' => guard = Some(((row, col), Direction::West)),
+                    _ => return None,
+                }
+            }
+        }
 
         Some(Board {
-            tiles,
+            obstructions,
             size: BoardSize { rows, cols },
+            guard: guard?,
         })
     }
 
-    fn print(board: &Board) {
-        for row in 0..board.size.rows {
-            for col in 0..board.size.cols {
-                let tile: &Tile = board.tiles.get(&(row, col)).unwrap();
-                let char = match tile {
-                    Tile::Unvisited => '.',
-                    Tile::Obstruction => '#',
-                    Tile::Guard(direction) => match direction {
-                        Direction::North => 'N',
-                        Direction::East => 'E',
-                        Direction::South => 'S',
-                        Direction::West => 'W',
-                    },
-                    Tile::VisitedPart1 => 'X',
-                    Tile::VisitedPart2(direction) => match direction {
-                        Direction::North => '^',
-                        Direction::East => '>',
-                        Direction::South => 'V',
-                        Direction::West => '<',
-                    },
-                };
-                print!("{}", char);
-            }
-            print!("\n")
-        }
+    fn is_obstruction(&self, pos: Coord) -> bool {
+        self.obstructions[pos.0 * self.size.cols + pos.1]
     }
-}
-
-fn step_part_1(tiles: &mut HashMap<(usize, usize), Tile>, size: &BoardSize) -> bool {
-    // let (guard_pos, guard_tile) = tiles.iter().find(|(pos, tile)| {
-    let (guard_pos, guard_tile) = find_guard(tiles);
-
-    tiles.insert(guard_pos, Tile::VisitedPart1);
 
-    let guard_direction = match guard_tile.clone() {
-        Tile::Guard(direction) => direction,
-        _ => panic!("Guard tile should always be guard"),
-    };
-
-    if let Some(pos_in_front) = pos_in_direction(guard_pos, &guard_direction, size) {
-        if let Some(tile_in_front) = tiles.get(&pos_in_front) {
-            match tile_in_front {
-                Tile::Unvisited | Tile::VisitedPart1 => {
-                    // Set tile in front as guard with same direction
-                    tiles.insert(pos_in_front, guard_tile);
-                    return false;
-                }
-                Tile::Obstruction => {
-                    let turned_direction = guard_direction.turn_right();
-                    tiles.insert(guard_pos, Tile::Guard(turned_direction));
-                    return false;
-                }
-                Tile::Guard(_) => {
-                    panic!("should not have more than one guard")
-                }
-                Tile::VisitedPart2(_) => panic!("Should not find these"),
-            }
+    // Moves a single step forward, or turns right if blocked. None when leaving the board.
+    fn step(&self, (pos, direction): &Pose) -> Option<Pose> {
+        let pos_in_front = pos_in_direction(*pos, direction, &self.size)?;
+        if self.is_obstruction(pos_in_front) {
+            Some((*pos, direction.turn_right()))
         } else {
-            panic!("should always find tile")
+            Some((pos_in_front, *direction))
         }
-    } else {
-        println!("Outside of board!");
-        true
     }
 }
 
-fn find_guard(tiles: &mut HashMap<(usize, usize), Tile>) -> ((usize, usize), Tile) {
-    let (guard_pos, guard_tile) = tiles
-        .iter()
-        .find(|(_, tile)| matches!(tile, Tile::Guard(_)))
-        .map(|(pos, tile)| (*pos, tile.clone()))
-        .expect("should find guard!");
-    (guard_pos, guard_tile)
-}
-
-struct StepResult {
-    found_loop: bool,
-    finished: bool,
-}
-
-#[derive(PartialEq, Eq, Hash)]
-struct SeenTile {
-    coord: Coord,
-    tile: Tile,
+// For every position and direction, the position the guard walks to before hitting an
+// obstruction, or None if the guard walks off the board.
+struct JumpTable {
+    jumps: Vec<Option<Coord>>,
+    size: BoardSize,
 }
 
-fn step_part_2(
-    tiles: &mut HashMap<Coord, Tile>,
-    seen: &mut HashSet<SeenTile>,
-    size: &BoardSize,
-) -> StepResult {
-    // let (guard_pos, guard_tile) = tiles.iter().find(|(pos, tile)| {
-    let (guard_pos, guard_tile) = tiles
-        .iter()
-        .find(|(_, tile)| matches!(tile, Tile::Guard(_)))
-        .map(|(pos, tile)| (*pos, tile.clone()))
-        .expect("part 2: should find guard!");
-
-    let seen_tile = SeenTile {
-        coord: guard_pos,
-        tile: guard_tile.clone(),
-    };
-    if seen.contains(&seen_tile) {
-        // Found loop!
-        let found_loop = true;
-        let finished = false;
-        return StepResult {
-            found_loop,
-            finished,
-        };
-    } else {
-        seen.insert(seen_tile);
-    }
-
-    tiles.insert(guard_pos, Tile::VisitedPart1);
-
-    let guard_direction = match guard_tile.clone() {
-        Tile::Guard(direction) => direction,
-        _ => panic!("Guard tile should always be guard"),
-    };
-
-    if let Some(pos_in_front) = pos_in_direction(guard_pos, &guard_direction, size) {
-        if let Some(tile_in_front) = tiles.get(&pos_in_front) {
-            match tile_in_front {
-                Tile::Unvisited | Tile::VisitedPart1 => {
-                    // Set tile in front as guard with same direction
-                    tiles.insert(pos_in_front, guard_tile);
-
-                    return StepResult {
-                        found_loop: false,
-                        finished: false,
-                    };
-                }
-                Tile::Obstruction => {
-                    let turned_direction = guard_direction.turn_right();
-                    tiles.insert(guard_pos, Tile::Guard(turned_direction));
-                    return StepResult {
-                        found_loop: false,
-                        finished: false,
+impl JumpTable {
+    fn new(board: &Board) -> JumpTable {
+        let size = board.size;
+        let mut jumps = vec![None; size.rows * size.cols * 4];
+
+        for direction in ALL_DIRECTIONS {
+            // Visit the positions furthest in the direction first, so the one in front is already known
+            let rows = (0..size.rows).collect_vec();
+            let cols = (0..size.cols).collect_vec();
+            let (rows, cols) = match direction {
+                Direction::North => (rows, cols),
+                Direction::South => (rows.into_iter().rev().collect(), cols),
+                Direction::West => (rows, cols),
+                Direction::East => (rows, cols.into_iter().rev().collect()),
+            };
+
+            for &row in &rows {
+                for &col in &cols {
+                    let pos = (row, col);
+                    let jump = match pos_in_direction(pos, &direction, &size) {
+                        None => None,
+                        Some(in_front) if board.is_obstruction(in_front) => Some(pos),
+                        Some(in_front) => jumps[Self::index(size, in_front, &direction)],
                     };
+                    jumps[Self::index(size, pos, &direction)] = jump;
                 }
-                Tile::Guard(_) => {
-                    panic!("should not have more than one guard")
-                }
-                Tile::VisitedPart2(_) => panic!("Should not find these"),
             }
-        } else {
-            panic!("should always find tile")
         }
-    } else {
-        // println!("Outside of board!");
-        return StepResult {
-            found_loop: false,
-            finished: true,
-        };
+
+        JumpTable { jumps, size }
     }
-}
 
-pub fn part_one(input: &str) -> Option<u32> {
-    let board = Board::parse(input)?;
-    let size = board.size.clone();
-    let mut tiles = board.tiles.clone();
-
-    let mut finished = false;
-    let max_steps = 10000;
-
-    let mut steps = 0;
-    while !finished && steps < max_steps {
-        // println!("{}", steps);
-        // let cloned_tiles = tiles.clone();
-        // Board::print(&Board { tiles: cloned_tiles, size });
-        // finished = step_part_1(&mut tiles, &size);
-        finished = step_part_1(&mut tiles, &size);
-        steps += 1;
+    fn index(size: BoardSize, pos: Coord, direction: &Direction) -> usize {
+        (pos.0 * size.cols + pos.1) * 4 + direction.index()
     }
 
-    let count: u32 = tiles
-        .clone()
-        .iter()
-        .filter(|(_, tile)| matches!(tile, Tile::VisitedPart1))
-        .count()
-        .try_into()
-        .unwrap();
+    // Walks to the next obstruction and turns right, also taking an extra obstruction into account
+    fn jump(&self, (pos, direction): &Pose, extra_obstruction: Coord) -> Option<Pose> {
+        let stop = self.jumps[Self::index(self.size, *pos, direction)];
+
+        if let Some(to_extra) = steps_ahead(*pos, extra_obstruction, direction) {
+            let to_stop = stop.and_then(|stop| steps_ahead(*pos, stop, direction));
+            let blocked_by_extra = match stop {
+                None => true,
+                Some(_) => to_extra <= to_stop.unwrap_or(0) + 1,
+            };
+            if blocked_by_extra {
+                let before_extra = pos_after_steps(*pos, direction, to_extra - 1);
+                return Some((before_extra, direction.turn_right()));
+            }
+        }
 
-    Some(count)
+        stop.map(|stop| (stop, direction.turn_right()))
+    }
 }
 
-// pub fn part_two(input: &str) -> Option<u32> {
-//     let board = Board::parse(input)?;
-//     let size = board.size.clone();
-//     let mut tiles = board.tiles.clone();
-
-//     let loop_count = loops(&mut tiles, &size, false);
-
-//     Some(loop_count)
-// }
-
-fn normal_path_coords(input: &str) -> Option<Vec<Coord>> {
-    let board = Board::parse(input)?;
-    let size = board.size.clone();
-    let mut tiles = board.tiles.clone();
-
-    let mut finished = false;
-    let max_steps = 15000;
-
-    let mut steps = 0;
-    while !finished && steps < max_steps {
-        // println!("{}", steps);
-        // let cloned_tiles = tiles.clone();
-        // Board::print(&Board { tiles: cloned_tiles, size });
-        // finished = step_part_1(&mut tiles, &size);
-        finished = step_part_1(&mut tiles, &size);
-        steps += 1;
+// The poses of the guard until it leaves the board, or None if it walks in a loop
+fn guard_path(board: &Board) -> Option<Vec<Pose>> {
+    match cycle::history(board.guard, |pose| board.step(pose)) {
+        (poses, None) => Some(poses),
+        (_, Some(_)) => None,
     }
-
-    let coords: Vec<Coord> = tiles
-        .clone()
-        .iter()
-        .filter(|(_, tile)| matches!(tile, Tile::VisitedPart1))
-        .map(|(coord, _)| *coord)
-        .collect();
-
-    Some(coords)
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
+pub fn part_one(input: &str) -> Option<u32> {
     let board = Board::parse(input)?;
-    let size = board.size.clone();
-    let mut tiles = board.tiles.clone();
-
-    let (guard_pos, _) = find_guard(&mut tiles);
-    let path_coords =normal_path_coords(input)?;
-    // dbg!(path_coords.len());
-    let variants =
-        path_coords.into_iter()
-        .filter(|coord| *coord != guard_pos)
-        .collect_vec();
-    // dbg!(variants.len());
+    let path = guard_path(&board)?;
 
-    let loop_count = variants
-        .into_iter()
-        .filter(|variant_coord| variant_has_loop(size, &tiles, *variant_coord))
-        .count()
-        .try_into()
-        .ok()?;
+    let count = path.iter().map(|(pos, _)| pos).unique().count();
 
-    Some(loop_count)
+    count.try_into().ok()
 }
 
-fn variant_has_loop(
-    size: BoardSize,
-    tiles: &HashMap<(usize, usize), Tile>,
-    variant_pos: (usize, usize),
-) -> bool {
-    let mut tile_variant = tiles.clone();
-    tile_variant.insert(variant_pos, Tile::Obstruction);
-
-    let mut seen: HashSet<SeenTile> = HashSet::new();
-
-    let mut finished = false;
-    let max_steps = 20000;
+fn variant_has_loop(board: &Board, jump_table: &JumpTable, variant_pos: Coord) -> bool {
+    cycle::find_cycle(board.guard, |pose| jump_table.jump(pose, variant_pos)).is_some()
+}
 
-    let mut steps = 0;
-    while !finished && steps < max_steps {
-        let result = step_part_2(&mut tile_variant, &mut seen, &size);
-        finished = result.finished;
+pub fn part_two(input: &str) -> Option<u32> {
+    let board = Board::parse(input)?;
+    let jump_table = JumpTable::new(&board);
 
-        if result.found_loop {
-            return true;
-        }
+    // An obstruction can only change the path if it's placed somewhere on it
+    let (guard_pos, _) = board.guard;
+    let variants = guard_path(&board)?
+        .into_iter()
+        .map(|(pos, _)| pos)
+        .filter(|pos| *pos != guard_pos)
+        .unique()
+        .collect_vec();
 
-        steps += 1;
-    }
+    let loop_count = variants
+        .par_iter()
+        .filter(|variant_pos| variant_has_loop(&board, &jump_table, **variant_pos))
+        .count();
 
-    false
+    loop_count.try_into().ok()
 }
 
 #[cfg(test)]
@@ -410,18 +276,72 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(6));
     }
-    // Note: 1396 too low
 
     #[test]
     fn test_part_two_small_loop() {
-        let result = part_two(&advent_of_code::template::read_file_part("examples", DAY, 1));
+        let result = part_two(&advent_of_code::template::read_file_part(
+            "examples", DAY, 1,
+        ));
         assert_eq!(result, Some(1));
     }
 
-
     #[test]
     fn test_part_two_small_loop2() {
-        let result = part_two(&advent_of_code::template::read_file_part("examples", DAY, 2));
+        let result = part_two(&advent_of_code::template::read_file_part(
+            "examples", DAY, 2,
+        ));
         assert_eq!(result, Some(1));
     }
+
+    #[test]
+    fn test_jump_matches_steps() {
+        let board = Board::parse(&advent_of_code::template::read_file("examples", DAY)).unwrap();
+        let jump_table = JumpTable::new(&board);
+
+        for row in 0..board.size.rows {
+            for col in 0..board.size.cols {
+                let extra = (row, col);
+                if board.is_obstruction(extra) || extra == board.guard.0 {
+                    continue;
+                }
+
+                let mut with_extra =
+                    Board::parse(&advent_of_code::template::read_file("examples", DAY)).unwrap();
+                with_extra.obstructions[row * board.size.cols + col] = true;
+
+                // Single steps, only keeping the poses right after turning
+                let stepped =
+                    std::iter::successors(Some(board.guard), |pose| with_extra.step(pose))
+                        .take(1000)
+                        .collect_vec();
+                let turns = stepped
+                    .iter()
+                    .tuple_windows()
+                    .filter(|(a, b)| a.0 == b.0)
+                    .map(|(_, b)| *b)
+                    .take(10)
+                    .collect_vec();
+
+                let mut jumped = vec![];
+                let mut pose = board.guard;
+                while let Some(next) = jump_table.jump(&pose, extra) {
+                    if jumped.len() == 10 {
+                        break;
+                    }
+                    jumped.push(next);
+                    pose = next;
+                }
+
+                assert_eq!(turns, jumped, "extra obstruction at {:?}", extra);
+            }
+        }
+    }
+
+    #[test]
+    fn test_long_path_without_loop() {
+        // Takes more steps to leave the board than the old step cap allowed
+        let input = format!(">{}", ".".repeat(25_000));
+        assert_eq!(part_one(&input), Some(25_001));
+        assert_eq!(part_two(&input), Some(0));
+    }
 }
@@ -1,6 +1,6 @@
-use std::cmp::max;
+use itertools::Itertools;
 
-advent_of_code::solution!(7);
+advent_of_code::solution!(7, debug = debug);
 
 #[derive(Debug, PartialEq)]
 struct CalibrationEquation {
@@ -21,115 +21,180 @@ impl CalibrationEquation {
     }
 }
 
-fn calibration_result_part_1(lhs: Option<u64>, rest: Vec<u64>, test_value: u64) -> u64 {
-    if rest.len() == 0 {
-        // Test if rest equals test_value
-        if lhs.expect("lhs must not be empty") == test_value {
-            return test_value;
-        } else {
-            return 0;
-        }
-    }
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operator {
+    Add,
+    Multiply,
+    Concatenate,
+}
 
-    if let Some(lhs_) = lhs {
-        let next = rest.get(0).expect("rest must not be empty").to_owned();
-        let next_rest = rest.get(1..).map_or(vec![], |x| x.to_vec());
+const PART_ONE_OPERATORS: &[Operator] = &[Operator::Add, Operator::Multiply];
+const PART_TWO_OPERATORS: &[Operator] = &[Operator::Add, Operator::Multiply, Operator::Concatenate];
 
-        max(
-            calibration_result_part_1(Some(lhs_ + next), next_rest.clone(), test_value),
-            calibration_result_part_1(Some(lhs_ * next), next_rest, test_value),
-        )
+fn concatenate(left: u64, right: u64) -> Option<u64> {
+    let right_digits = right.checked_ilog10().unwrap_or(0) + 1;
+    left.checked_mul(10u64.checked_pow(right_digits)?)?
+        .checked_add(right)
+}
+
+// The left operand of a concatenation giving `result`, if `result` ends with the digits of `right`
+fn unconcatenate(result: u64, right: u64) -> Option<u64> {
+    let right_digits = right.checked_ilog10().unwrap_or(0) + 1;
+    let shift = 10u64.checked_pow(right_digits)?;
+    if result % shift == right {
+        Some(result / shift)
     } else {
-        let next = rest.get(0).expect("rest must not be empty").to_owned();
-        let next_rest = rest.get(1..).map_or(vec![], |x| x.to_vec());
+        None
+    }
+}
+
+impl Operator {
+    fn apply(&self, left: u64, right: u64) -> Option<u64> {
+        match self {
+            Operator::Add => left.checked_add(right),
+            Operator::Multiply => left.checked_mul(right),
+            Operator::Concatenate => concatenate(left, right),
+        }
+    }
 
-        calibration_result_part_1(Some(next), next_rest, test_value)
+    // The left operand that gives `result` together with `right`, if there is one. Operands are
+    // positive in the puzzle, so multiplying by zero is never inverted.
+    fn invert(&self, result: u64, right: u64) -> Option<u64> {
+        match self {
+            Operator::Add => result.checked_sub(right),
+            Operator::Multiply if right != 0 && result.is_multiple_of(right) => {
+                Some(result / right)
+            }
+            Operator::Multiply => None,
+            Operator::Concatenate => unconcatenate(result, right),
+        }
+    }
+
+    fn symbol(&self) -> &'static str {
+        match self {
+            Operator::Add => "+",
+            Operator::Multiply => "*",
+            Operator::Concatenate => "||",
+        }
     }
 }
 
-fn concatenate(left: u64, right: u64) -> u64 {
-    let right_digits = right.checked_ilog10().unwrap_or(0) + 1;
-    (left * 10u64.pow(right_digits)) + right
+// Evaluates the operators between the values from left to right
+fn evaluate(values: &[u64], operators: &[Operator]) -> Option<u64> {
+    let (first, rest) = values.split_first()?;
+    if rest.len() != operators.len() {
+        return None;
+    }
+    operators
+        .iter()
+        .zip(rest)
+        .try_fold(*first, |acc, (operator, value)| operator.apply(acc, *value))
 }
 
-fn concatenate_first_two(lhs: u64, rest: Vec<u64>) -> Vec<u64> {
-    match rest.len() {
-        0 => vec![lhs],
-        1 => vec![concatenate(lhs, rest[0])],
-        _ => {
-            let mut beginning = vec![concatenate(lhs, rest[0])];
-            let mut rest = rest.get(1..).unwrap().to_vec();
-            beginning.append(&mut rest);
-            beginning
+// Undoes the operators from the last value, so every operator that can't give the target is
+// pruned before trying the values in front of it. Pushes the operators found in reverse order.
+fn solve_backward(
+    target: u64,
+    values: &[u64],
+    operators: &[Operator],
+    found: &mut Vec<Operator>,
+) -> bool {
+    let Some((last, rest)) = values.split_last() else {
+        return false;
+    };
+    if rest.is_empty() {
+        return target == *last;
+    }
+
+    for operator in operators {
+        if let Some(left) = operator.invert(target, *last) {
+            found.push(*operator);
+            if solve_backward(left, rest, operators, found) {
+                return true;
+            }
+            found.pop();
         }
     }
+    false
 }
 
-fn calibration_result_part_2(lhs: Option<u64>, rest: Vec<u64>, test_value: u64) -> u64 {
-    if rest.len() == 0 {
-        // Test if rest equals test_value
-        if lhs.expect("lhs must not be empty") == test_value {
-            return test_value;
+impl CalibrationEquation {
+    // Operators between the values that make the equation true, if any
+    fn solve(&self, operators: &[Operator]) -> Option<Vec<Operator>> {
+        let mut found = vec![];
+        if !solve_backward(self.test_value, &self.values, operators, &mut found) {
+            return None;
+        }
+        found.reverse();
+
+        // Double check the assignment going forward
+        if evaluate(&self.values, &found) == Some(self.test_value) {
+            Some(found)
         } else {
-            return 0;
+            None
         }
     }
 
-    if let Some(lhs_) = lhs {
-        let next = rest.get(0).expect("rest must not be empty").to_owned();
-        let next_rest = rest.get(1..).map_or(vec![], |x| x.to_vec());
-
-        let concatenate_variant: u64 =
-            calibration_result_part_2(Some(concatenate(lhs_, next)), next_rest.clone(), test_value);
-
-        max(
-            max(
-                calibration_result_part_2(Some(lhs_ + next), next_rest.clone(), test_value),
-                calibration_result_part_2(Some(lhs_ * next), next_rest, test_value),
-            ),
-            concatenate_variant,
+    // The equation with the operators filled in, e.g. 190 = 10 * 19
+    fn explain(&self, operators: &[Operator]) -> String {
+        let values = self.values.iter().map(|value| value.to_string());
+        let symbols = operators
+            .iter()
+            .map(|operator| operator.symbol().to_string());
+        format!(
+            "{} = {}",
+            self.test_value,
+            values.interleave(symbols).join(" ")
         )
-    } else {
-        let next = rest.get(0).expect("rest must not be empty").to_owned();
-        let next_rest = rest.get(1..).map_or(vec![], |x| x.to_vec());
-
-        calibration_result_part_2(Some(next), next_rest, test_value)
     }
 }
 
-pub fn part_one(input: &str) -> Option<u64> {
-    let calibration_eqs = input
-        .lines()
-        .map(CalibrationEquation::parse)
-        .collect::<Option<Vec<_>>>()
-        .expect("should parse");
+fn parse_equations(input: &str) -> Option<Vec<CalibrationEquation>> {
+    input.lines().map(CalibrationEquation::parse).collect()
+}
+
+fn total_calibration_result(input: &str, operators: &[Operator]) -> Option<u64> {
+    let calibration_eqs = parse_equations(input)?;
 
     let total_calibration_result: u64 = calibration_eqs
         .iter()
-        .map(|eq| calibration_result_part_1(None, eq.values.clone(), eq.test_value))
+        .filter(|eq| eq.solve(operators).is_some())
+        .map(|eq| eq.test_value)
         .sum();
 
     Some(total_calibration_result)
 }
 
-pub fn part_two(input: &str) -> Option<u64> {
-    let calibration_eqs = input
-        .lines()
-        .map(CalibrationEquation::parse)
-        .collect::<Option<Vec<_>>>()
-        .expect("should parse");
+// Every equation that can be made true, with the operators filled in
+fn debug(input: &str) -> Option<String> {
+    let calibration_eqs = parse_equations(input)?;
+    Some(
+        calibration_eqs
+            .iter()
+            .filter_map(|eq| Some(eq.explain(&eq.solve(PART_TWO_OPERATORS)?)))
+            .join("\n"),
+    )
+}
 
-    let total_calibration_result: u64 = calibration_eqs
-        .iter()
-        .map(|eq| calibration_result_part_2(None, eq.values.clone(), eq.test_value))
-        .sum();
+pub fn part_one(input: &str) -> Option<u64> {
+    total_calibration_result(input, PART_ONE_OPERATORS)
+}
 
-    Some(total_calibration_result)
+pub fn part_two(input: &str) -> Option<u64> {
+    total_calibration_result(input, PART_TWO_OPERATORS)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    fn explained(line: &str, operators: &[Operator]) -> Option<String> {
+        let eq = CalibrationEquation::parse(line).unwrap();
+        let found = eq.solve(operators)?;
+        assert_eq!(evaluate(&eq.values, &found), Some(eq.test_value));
+        Some(eq.explain(&found))
+    }
+
     #[test]
     fn test_parse() {
         let result = CalibrationEquation::parse("3267: 81 40 27");
@@ -144,16 +209,63 @@ mod tests {
 
     #[test]
     fn test_concatenate() {
-        assert_eq!(156, concatenate(15, 6));
-        assert_eq!(1560, concatenate(15, 60));
+        assert_eq!(Some(156), concatenate(15, 6));
+        assert_eq!(Some(1560), concatenate(15, 60));
+        assert_eq!(None, concatenate(u64::MAX / 10, 60));
+    }
+
+    #[test]
+    fn test_unconcatenate() {
+        assert_eq!(Some(15), unconcatenate(156, 6));
+        assert_eq!(Some(15), unconcatenate(1560, 60));
+        assert_eq!(Some(0), unconcatenate(6, 6));
+        assert_eq!(None, unconcatenate(156, 5));
+        assert_eq!(None, unconcatenate(156, 16));
     }
 
     #[test]
-    fn test_concatenate_first_two() {
-        assert_eq!(vec![315, 6], concatenate_first_two(3, vec![15, 6]));
-        assert_eq!(vec![3156], concatenate_first_two(3, vec![156]));
-        assert_eq!(vec![3], concatenate_first_two(3, vec![]));
-        assert_eq!(vec![315, 6, 4], concatenate_first_two(3, vec![15, 6, 4]));
+    fn test_evaluate() {
+        let operators = [
+            Operator::Multiply,
+            Operator::Concatenate,
+            Operator::Multiply,
+        ];
+        assert_eq!(evaluate(&[6, 8, 6, 15], &operators), Some(7290));
+        assert_eq!(evaluate(&[6, 8], &operators), None);
+    }
+
+    #[test]
+    fn test_explain_part_one() {
+        assert_eq!(
+            explained("190: 10 19", PART_ONE_OPERATORS),
+            Some("190 = 10 * 19".to_string())
+        );
+        assert_eq!(
+            explained("3267: 81 40 27", PART_ONE_OPERATORS),
+            Some("3267 = 81 * 40 + 27".to_string())
+        );
+        assert_eq!(
+            explained("292: 11 6 16 20", PART_ONE_OPERATORS),
+            Some("292 = 11 + 6 * 16 + 20".to_string())
+        );
+        assert_eq!(explained("156: 15 6", PART_ONE_OPERATORS), None);
+        assert_eq!(explained("21037: 9 7 18 13", PART_TWO_OPERATORS), None);
+    }
+
+    #[test]
+    fn test_explain_part_two() {
+        assert_eq!(
+            explained("156: 15 6", PART_TWO_OPERATORS),
+            Some("156 = 15 || 6".to_string())
+        );
+        assert_eq!(
+            explained("7290: 6 8 6 15", PART_TWO_OPERATORS),
+            Some("7290 = 6 * 8 || 6 * 15".to_string())
+        );
+        assert_eq!(
+            explained("192: 17 8 14", PART_TWO_OPERATORS),
+            Some("192 = 17 || 8 + 14".to_string())
+        );
     }
 
     #[test]
@@ -171,7 +283,7 @@ mod tests {
     #[test]
     fn test_part_two_156() {
         assert_eq!(Some(156), part_two("156: 15 6"));
-        // assert_eq!(Some(7290), part_two("7290: 6 8 6 15"));
-        // assert_eq!(Some(192), part_two("192: 17 8 14"));
+        assert_eq!(Some(7290), part_two("7290: 6 8 6 15"));
+        assert_eq!(Some(192), part_two("192: 17 8 14"));
     }
 }
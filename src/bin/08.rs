@@ -2,181 +2,177 @@ use std::collections::HashSet;
 
 use itertools::Itertools;
 
-advent_of_code::solution!(8);
+advent_of_code::solution!(8, debug = debug);
 
-#[derive(Clone, Copy)]
-struct Node {
-    pos: (usize, usize),
-    c: char,
-}
+// Coordinates in format (row, col). Signed, since antinodes may fall outside the map.
+type Position = (isize, isize);
 
-impl Node {
-    fn parse_all(input: &str) -> Vec<Node> {
-        input
-            .lines()
-            .enumerate()
-            .flat_map(|(row, line)| {
-                line.chars()
-                    .enumerate()
-                    .map(move |(col, c)| Node { pos: (row, col), c })
-            })
-            .filter(|node| node.c != '.') // Placeholder for empty
-            .collect()
-    }
-}
+const EMPTY: char = '.';
+const ANTINODE: char = '#';
 
-fn is_within_bounds(pos: (i32, i32), rows: i32, cols: i32) -> bool {
-    return (0 <= pos.0) && (pos.0 < rows) && (0 <= pos.1) && (pos.1 < cols);
+// Which multiples of the distance between two antennas have antinodes, measured from either
+// antenna away from the other one. `None` leaves that end unbounded.
+#[derive(Clone, Copy, Debug)]
+struct Harmonics {
+    min: Option<isize>,
+    max: Option<isize>,
 }
 
-fn pos_as_i32(pos: (usize, usize)) -> (i32, i32) {
-    (pos.0 as i32, pos.1 as i32)
+// Only the point twice as far from one antenna as from the other
+const FIRST_HARMONIC: Harmonics = Harmonics {
+    min: Some(1),
+    max: Some(1),
+};
+
+// Every point in line with the antennas, including the antennas themselves
+const ALL_HARMONICS: Harmonics = Harmonics {
+    min: None,
+    max: None,
+};
+
+impl Harmonics {
+    fn contains(&self, multiple: isize, divisor: isize) -> bool {
+        self.min.is_none_or(|min| multiple >= min * divisor)
+            && self.max.is_none_or(|max| multiple <= max * divisor)
+    }
 }
 
-fn antinode_positions_part_1(
-    pos1: (usize, usize),
-    pos2: (usize, usize),
-    rows: i32,
-    cols: i32,
-) -> Vec<(usize, usize)> {
-    let p1 = pos_as_i32(pos1);
-    let p2 = pos_as_i32(pos2);
-
-    // x_1 = 5, x_2 = 3 => x_anti_1 = 5 - 2*(5-3)
-    let candidate_1 = (p1.0 - 2 * (p1.0 - p2.0), p1.1 - 2 * (p1.1 - p2.1));
-    let candidate_2 = (p2.0 - 2 * (p2.0 - p1.0), p2.1 - 2 * (p2.1 - p1.1));
-
-    vec![candidate_1, candidate_2]
-        .iter()
-        .filter(|candidate| is_within_bounds(**candidate, rows, cols))
-        .map(|pos| (pos.0 as usize, pos.1 as usize))
-        .collect()
+#[derive(Clone, Copy, Debug)]
+struct Antenna {
+    pos: Position,
+    frequency: char,
 }
 
-fn add(a: (i32, i32), b: (i32, i32)) -> (i32, i32) {
-    (a.0 + b.0, a.1 + b.1)
+struct AntennaMap {
+    rows: isize,
+    cols: isize,
+    antennas: Vec<Antenna>,
 }
 
-fn minus(a: (i32, i32), b: (i32, i32)) -> (i32, i32) {
-    (a.0 - b.0, a.1 - b.1)
-}
+impl AntennaMap {
+    fn parse(input: &str) -> Option<AntennaMap> {
+        let rows = input.lines().count() as isize;
+        let cols = input.lines().next()?.len() as isize;
+        let antennas = input
+            .lines()
+            .enumerate()
+            .flat_map(|(row, line)| {
+                line.chars()
+                    .enumerate()
+                    .map(move |(col, frequency)| Antenna {
+                        pos: (row as isize, col as isize),
+                        frequency,
+                    })
+            })
+            .filter(|antenna| antenna.frequency != EMPTY)
+            .collect();
 
-fn multiply(vec: (i32, i32), scalar: i32) -> (i32, i32) {
-    (vec.0 * scalar, vec.1 * scalar)
-}
+        Some(AntennaMap {
+            rows,
+            cols,
+            antennas,
+        })
+    }
 
-fn antinode_positions_part_2(
-    pos1: (usize, usize),
-    pos2: (usize, usize),
-    rows: i32,
-    cols: i32,
-) -> Vec<(usize, usize)> {
-    let p1 = pos_as_i32(pos1);
-    let p2 = pos_as_i32(pos2);
-
-    // x_1 = 5, x_2 = 3 => x_anti_1 = 5 - 2*(5-3)
-    // x_1 = 5, x_2 = 3 => diff = 2
-    //                  => add diff from 1 and subtract from 2
-    //                     BUT - we also want the nodes themselves to be antinodes, so we swap them here
-    let diff = ((p1.0 - p2.0), (p1.1 - p2.1));
-
-    let mut antinode_positions: Vec<(usize, usize)> = vec![];
-
-    for i in 1.. {
-        let scaled_diff = multiply(diff, i);
-        let candidate = add(p2, scaled_diff);
-
-        if is_within_bounds(candidate, rows, cols) {
-            antinode_positions.push((candidate.0 as usize, candidate.1 as usize));
-        } else {
-            break
-        }
+    fn contains(&self, pos: Position) -> bool {
+        (0..self.rows).contains(&pos.0) && (0..self.cols).contains(&pos.1)
     }
 
-    for i in 1.. {
-        let scaled_diff = multiply(diff, -i);
-        let candidate = add(p1, scaled_diff);
+    // Antinodes on the line through both antennas, on the side of `from`. The line is walked in
+    // steps reduced by the gcd of the distance, so grid points in between are found too.
+    fn line_antinodes(
+        &self,
+        from: Position,
+        other: Position,
+        harmonics: Harmonics,
+    ) -> Vec<Position> {
+        let diff = (from.0 - other.0, from.1 - other.1);
+        let divisor = gcd(diff.0.unsigned_abs(), diff.1.unsigned_abs()) as isize;
+        let step = (diff.0 / divisor, diff.1 / divisor);
+
+        // The points on the map form a single stretch of the line, which `from` is on
+        let walk = |first: isize, direction: isize| {
+            (first..)
+                .map(move |i| i * direction)
+                .map(move |multiple| {
+                    (
+                        multiple,
+                        (from.0 + multiple * step.0, from.1 + multiple * step.1),
+                    )
+                })
+                .take_while(|(_, pos)| self.contains(*pos))
+                .filter(|(multiple, _)| harmonics.contains(*multiple, divisor))
+                .map(|(_, pos)| pos)
+        };
+        walk(0, 1).chain(walk(1, -1)).collect()
+    }
 
-        if is_within_bounds(candidate, rows, cols) {
-            antinode_positions.push((candidate.0 as usize, candidate.1 as usize));
-        } else {
-            break
-        }
+    fn antinodes(&self, harmonics: Harmonics) -> HashSet<Position> {
+        self.antennas
+            .iter()
+            .into_group_map_by(|antenna| antenna.frequency)
+            .values()
+            .flat_map(|group| group.iter().tuple_combinations())
+            .flat_map(|(a, b)| {
+                let mut antinodes = self.line_antinodes(a.pos, b.pos, harmonics);
+                antinodes.extend(self.line_antinodes(b.pos, a.pos, harmonics));
+                antinodes
+            })
+            .collect()
     }
 
-    antinode_positions
+    // The map with the antinodes marked, like in the puzzle text. Antennas hide the antinodes
+    // under them.
+    fn render(&self, antinodes: &HashSet<Position>) -> String {
+        (0..self.rows)
+            .map(|row| {
+                (0..self.cols)
+                    .map(|col| {
+                        let antenna = self
+                            .antennas
+                            .iter()
+                            .find(|antenna| antenna.pos == (row, col));
+                        match antenna {
+                            Some(antenna) => antenna.frequency,
+                            None if antinodes.contains(&(row, col)) => ANTINODE,
+                            None => EMPTY,
+                        }
+                    })
+                    .collect::<String>()
+            })
+            .join("\n")
+    }
 }
 
-pub fn part_one(input: &str) -> Option<u32> {
-    // Find the size of the board
-    let rows = input.lines().collect_vec().len() as i32;
-    let cols = input.lines().nth(0).map(|x| x.len())? as i32;
-
-    // Parse all positions (row, col) and chars for nodes
-    let nodes = Node::parse_all(input);
-
-    // Find all potential antinodes
-    let antinodes: Vec<(usize, usize)> = nodes
-        .iter()
-        .into_group_map_by(|x| x.c)
-        .values()
-        .flat_map(|group| -> Vec<(usize, usize)> {
-            let combinations: Vec<(Node, Node)> = group
-                .iter()
-                .tuple_combinations::<(_, _)>()
-                .map(|(a, b)| (**a, **b))
-                .collect();
-            let group_antinode_positions = combinations
-                .iter()
-                .flat_map(|(a, b)| antinode_positions_part_1(a.pos, b.pos, rows, cols))
-                .collect::<Vec<(usize, usize)>>();
-            group_antinode_positions
-        })
-        .collect();
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
 
-    // Create hashset of all positions
-    let positions: HashSet<(usize, usize)> = antinodes.into_iter().collect();
+fn count_antinodes(input: &str, harmonics: Harmonics) -> Option<u32> {
+    let map = AntennaMap::parse(input)?;
+    map.antinodes(harmonics).len().try_into().ok()
+}
 
-    // Get count of elements in hashset
-    let count = positions.into_iter().count();
+// The map with the antinodes of both parts marked
+fn debug(input: &str) -> Option<String> {
+    let map = AntennaMap::parse(input)?;
+    Some(format!(
+        "{}\n\n{}",
+        map.render(&map.antinodes(FIRST_HARMONIC)),
+        map.render(&map.antinodes(ALL_HARMONICS))
+    ))
+}
 
-    Some(count as u32)
+pub fn part_one(input: &str) -> Option<u32> {
+    count_antinodes(input, FIRST_HARMONIC)
 }
 
 pub fn part_two(input: &str) -> Option<u32> {
-    // Find the size of the board
-    let rows = input.lines().collect_vec().len() as i32;
-    let cols = input.lines().nth(0).map(|x| x.len())? as i32;
-
-    // Parse all positions (row, col) and chars for nodes
-    let nodes = Node::parse_all(input);
-
-    // Find all potential antinodes
-    let antinodes: Vec<(usize, usize)> = nodes
-        .iter()
-        .into_group_map_by(|x| x.c)
-        .values()
-        .flat_map(|group| -> Vec<(usize, usize)> {
-            let combinations: Vec<(Node, Node)> = group
-                .iter()
-                .tuple_combinations::<(_, _)>()
-                .map(|(a, b)| (**a, **b))
-                .collect();
-            let group_antinode_positions = combinations
-                .iter()
-                .flat_map(|(a, b)| antinode_positions_part_2(a.pos, b.pos, rows, cols))
-                .collect::<Vec<(usize, usize)>>();
-            group_antinode_positions
-        })
-        .collect();
-
-    // Create hashset of all positions
-    let positions: HashSet<(usize, usize)> = antinodes.into_iter().collect();
-
-    // Get count of elements in hashset
-    let count = positions.into_iter().count();
-
-    Some(count as u32)
+    count_antinodes(input, ALL_HARMONICS)
 }
 
 #[cfg(test)]
@@ -194,4 +190,66 @@ mod tests {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(34));
     }
+
+    #[test]
+    fn test_render() {
+        let map = AntennaMap::parse(&advent_of_code::template::read_file("examples", DAY)).unwrap();
+        let expected = [
+            "##....#....#",
+            ".#.#....0...",
+            "..#.#0....#.",
+            "..##...0....",
+            "....0....#..",
+            ".#...#A....#",
+            "...#..#.....",
+            "#....#.#....",
+            "..#.....A...",
+            "....#....A..",
+            ".#........#.",
+            "...#......##",
+        ];
+        assert_eq!(
+            map.render(&map.antinodes(ALL_HARMONICS)),
+            expected.join("\n")
+        );
+    }
+
+    #[test]
+    fn test_in_between_points() {
+        // The antennas are two steps apart, so there's a grid point halfway between them
+        let map = AntennaMap::parse("a....\n.....\n..a..\n.....\n.....").unwrap();
+        assert_eq!(map.antinodes(FIRST_HARMONIC), HashSet::from([(4, 4)]));
+        assert_eq!(
+            map.antinodes(ALL_HARMONICS),
+            HashSet::from([(0, 0), (1, 1), (2, 2), (3, 3), (4, 4)])
+        );
+
+        // Half multiples only exist because of the in-between points
+        let halfway = Harmonics {
+            min: Some(0),
+            max: Some(1),
+        };
+        assert_eq!(
+            map.antinodes(halfway),
+            HashSet::from([(0, 0), (2, 2), (3, 3), (4, 4)])
+        );
+    }
+
+    #[test]
+    fn test_harmonic_range() {
+        let map = AntennaMap::parse("..........\nb.b.......").unwrap();
+        let second_to_third = Harmonics {
+            min: Some(2),
+            max: Some(3),
+        };
+        assert_eq!(
+            map.antinodes(second_to_third),
+            HashSet::from([(1, 6), (1, 7), (1, 8)])
+        );
+        let beyond_first = Harmonics {
+            min: Some(2),
+            max: None,
+        };
+        assert_eq!(map.antinodes(beyond_first).len(), 4);
+    }
 }
@@ -1,263 +1,254 @@
-use std::collections::VecDeque;
-
-use itertools::Itertools;
+use std::{cmp::Reverse, collections::BinaryHeap, fmt};
 
 advent_of_code::solution!(9);
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-// struct DiskMapEntry {
-//     blocks: u64,
-//     skip: u64,
-// }
-struct DiskMapEntry {
-    blocks: usize,
-    skip: usize,
-}
+// Every span in the disk map is a single digit
+const MAX_SPAN: usize = 9;
 
-impl DiskMapEntry {
-    fn parse(s: Vec<char>) -> Option<DiskMapEntry> {
-        match s.len() {
-            2 => Some(DiskMapEntry {
-                blocks: s[0].to_string().parse().ok()?,
-                skip: s[1].to_string().parse().ok()?,
-            }),
-            1 => Some(DiskMapEntry {
-                blocks: s[0].to_string().parse().ok()?,
-                skip: 0,
-            }),
-            _ => panic!(),
-        }
-    }
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Span {
+    start: usize,
+    len: usize,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
-struct IdentifiedEntry {
+// Consecutive blocks belonging to the file with the id
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Segment {
     id: usize,
-    entry: DiskMapEntry,
-}
-
-#[derive(Clone)]
-struct DiskMap {
-    entries: Vec<DiskMapEntry>,
+    span: Span,
 }
 
-impl DiskMap {
-    fn parse(s: &str) -> Option<DiskMap> {
-        let entries = s
-            .chars()
-            .chunks(2)
-            .into_iter()
-            .map(|spec| {
-                let chars = spec.collect::<Vec<char>>();
-                DiskMapEntry::parse(chars)
-            })
-            .collect::<Option<Vec<_>>>()?;
-        Some(DiskMap { entries })
+impl Segment {
+    // Sum of position * id over the blocks, using the sum of the arithmetic series of positions
+    fn checksum(&self) -> u64 {
+        let Span { start, len } = self.span;
+        let position_sum = len * start + len * len.saturating_sub(1) / 2;
+        (self.id * position_sum) as u64
     }
+}
 
-    fn spread(&self) -> Vec<Option<u32>> {
-        let mut out: Vec<Option<u32>> = vec![];
-        for (id, entry) in self.entries.iter().enumerate() {
-            for _ in 0..entry.blocks {
-                out.push(Some(id as u32))
-            }
+#[derive(Clone, Debug)]
+struct Disk {
+    // Ordered by position
+    segments: Vec<Segment>,
+    // Free space before compacting. Ordered by position
+    free: Vec<Span>,
+    len: usize,
+}
 
-            // Last entry will not have skip
-            for _ in 0..entry.skip {
-                out.push(None);
+impl Disk {
+    fn parse(s: &str) -> Option<Disk> {
+        let mut segments = vec![];
+        let mut free = vec![];
+        let mut pos = 0;
+
+        for (i, c) in s.trim().chars().enumerate() {
+            let len = c.to_digit(10)? as usize;
+            let span = Span { start: pos, len };
+            if i % 2 == 0 {
+                segments.push(Segment { id: i / 2, span });
+            } else if len > 0 {
+                free.push(span);
             }
+            pos += len;
         }
-        out
+
+        Some(Disk {
+            segments,
+            free,
+            len: pos,
+        })
     }
-}
 
-// fn spread_part_2(id_entries: &VecDeque<IdentifiedEntry>) -> Vec<Option<u64>> {
-fn spread_part_2(id_entries: &Vec<IdentifiedEntry>) -> Vec<Option<u64>> {
-    let mut out: Vec<Option<u64>> = vec![];
-    for id_entry in id_entries.iter() {
-        for _ in 0..id_entry.entry.blocks {
-            out.push(Some(id_entry.id.try_into().expect("Should convert u32 to u64")));
+    // Moves single blocks from the end of the disk to the leftmost free block, until there are
+    // no gaps. Expects a disk that has just been parsed.
+    fn compact_blocks(&self) -> Disk {
+        let mut files = self.segments.clone();
+        let mut moved: Vec<Segment> = vec![];
+        let mut last = files.len().checked_sub(1);
+
+        for free in &self.free {
+            let mut free = *free;
+            while free.len > 0 {
+                let Some(l) = last else {
+                    break;
+                };
+                let file = &mut files[l];
+                if file.span.len == 0 {
+                    last = l.checked_sub(1);
+                    continue;
+                }
+                if file.span.start <= free.start {
+                    break;
+                }
+
+                // The blocks are taken from the end of the file, which stays where it was
+                let n = free.len.min(file.span.len);
+                moved.push(Segment {
+                    id: file.id,
+                    span: Span {
+                        start: free.start,
+                        len: n,
+                    },
+                });
+                file.span.len -= n;
+                free.start += n;
+                free.len -= n;
+            }
         }
 
-        // Last entry will not have skip
-        for _ in 0..id_entry.entry.skip {
-            out.push(None);
+        let mut segments = files
+            .into_iter()
+            .filter(|file| file.span.len > 0)
+            .chain(moved)
+            .collect::<Vec<_>>();
+        segments.sort_by_key(|segment| segment.span.start);
+
+        Disk {
+            segments,
+            free: vec![],
+            len: self.len,
         }
     }
-    out
-}
 
-fn spread_checksum_part_2(id_entries: &Vec<IdentifiedEntry>) -> usize {
-    let mut checksum = 0;
-    let mut pos: usize = 0;
-    for id_entry in id_entries.iter() {
-        for i in 0..id_entry.entry.blocks {
-            let contr = (pos + i) * id_entry.id;
-            checksum += contr;
+    // Moves whole files, starting with the highest id, to the leftmost free span they fit in.
+    // Expects a disk that has just been parsed.
+    fn compact_files(&self) -> Disk {
+        // Start positions of the free spans, with a min-heap for every span length
+        let mut free_by_len: [BinaryHeap<Reverse<usize>>; MAX_SPAN + 1] =
+            std::array::from_fn(|_| BinaryHeap::new());
+        for free in &self.free {
+            free_by_len[free.len].push(Reverse(free.start));
         }
-        pos += id_entry.entry.blocks + id_entry.entry.skip;
-    }
-    checksum
-}
-
-fn checksum(numbers: Vec<u32>) -> u64 {
-    let mut out: u64 = 0;
-    for (pos_usize, idu32) in numbers.iter().enumerate() {
-        let id: u64 = u64::try_from(*idu32).unwrap();
-        let pos: u64 = pos_usize.try_into().unwrap();
-        let contribution = id * pos;
-        out += contribution
-    }
-    // let out = numbers
-    //     .iter()
-    //     .enumerate()
-    //     .map(|(i, x)| (u64::try_from(i).unwrap()) * (u64::try_from(*x).unwrap()))
-    //     .sum();
-    out
-}
-
-fn checksum_part_2(numbers: Vec<Option<u64>>) -> u64 {
-    let out = numbers
-        .iter()
-        .enumerate()
-        .map(|(i, x)| {
-            if let Some(id) = x {
-                let pos: u64 = i.try_into().unwrap();
-                pos * id
-            } else { 0 }
-        })
-        .sum();
-    out
-}
 
+        let mut segments = self.segments.clone();
+        for file in segments.iter_mut().rev() {
+            let len = file.span.len;
+            if len == 0 {
+                continue;
+            }
 
-pub fn part_one(input: &str) -> Option<u64> {
-    let diskmap = DiskMap::parse(input)?;
-    let mut spreaded = diskmap.spread();
-
-    // Dual pointer approach
-    let mut l = 0;
-    let mut r = spreaded.len() - 1;
-
-    while l < r {
-        while spreaded[l].is_some() && l < r {
-            l += 1;
+            // The leftmost of the first spans of every length the file fits in
+            let target = (len..=MAX_SPAN)
+                .filter_map(|free_len| {
+                    let Reverse(start) = free_by_len[free_len].peek()?;
+                    Some((*start, free_len))
+                })
+                .filter(|(start, _)| *start < file.span.start)
+                .min();
+
+            // Files only move left, so the space a file leaves behind is never used
+            if let Some((start, free_len)) = target {
+                free_by_len[free_len].pop();
+                if free_len > len {
+                    free_by_len[free_len - len].push(Reverse(start + len));
+                }
+                file.span.start = start;
+            }
         }
+        segments.sort_by_key(|segment| segment.span.start);
 
-        while spreaded[r].is_none() && l < r {
-            r -= 1
-        }
-        if l < r {
-            spreaded.swap(l, r);
+        Disk {
+            segments,
+            free: vec![],
+            len: self.len,
         }
     }
 
-    let (first_none_idx, _) = spreaded.iter().find_position(|x| x.is_none())?;
-    let numbers = spreaded
-        .iter()
-        .take(first_none_idx)
-        .copied()
-        .collect::<Option<Vec<u32>>>()?;
-
-    Some(checksum(numbers))
+    fn checksum(&self) -> u64 {
+        self.segments.iter().map(Segment::checksum).sum()
+    }
 }
 
-fn as_string(vec: &Vec<Option<u64>>) -> String {
-    let mut out: Vec<char> = vec![];
-    for maybe_id in vec {
-        if let Some(id_entry) = maybe_id {
-            let s = id_entry.to_string();
-            for c in s.chars() {
-                out.push(c);
-            }
-        } else {
-            out.push('.');
+// Renders the blocks of the disk like the puzzle does, e.g. 00...111...2...333.44
+impl fmt::Display for Disk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut pos = 0;
+        for segment in &self.segments {
+            write!(f, "{}", ".".repeat(segment.span.start - pos))?;
+            write!(f, "{}", segment.id.to_string().repeat(segment.span.len))?;
+            pos = segment.span.start + segment.span.len;
         }
+        write!(f, "{}", ".".repeat(self.len - pos))
     }
-    out.into_iter().collect()
 }
 
-pub fn part_two(input: &str) -> Option<u64> {
-    let diskmap = DiskMap::parse(input)?;
-
-    let entries = diskmap
-        .entries
-        .into_iter()
-        .collect::<VecDeque<DiskMapEntry>>();
-
-    let mut identified_entries: Vec<IdentifiedEntry> = entries
-        .iter()
-        .enumerate()
-        .map(|(id, entry)| IdentifiedEntry { id, entry: entry.clone() })
-        .collect();
-
-    let num_entries = entries.len();
-
-    for id_to_move in (0..num_entries).rev() {
-        let (mover_idx, mover_identified_entry) = identified_entries
-            .clone()
-            .into_iter()
-            .find_position(|entry| entry.id == id_to_move)
-            .expect("Should find entry to move");
-
-        if let Some((target_idx, target_identified_entry)) =
-            identified_entries.clone().into_iter().take(mover_idx).find_position(|target_candidate| {
-                target_candidate.entry.skip >= mover_identified_entry.entry.blocks
-            })
-        {
-            let mut new_mover = mover_identified_entry.clone();
-            let before_mover_idx = mover_idx - 1;
-            identified_entries[before_mover_idx].entry.skip += new_mover.entry.blocks + new_mover.entry.skip;
+pub fn part_one(input: &str) -> Option<u64> {
+    let disk = Disk::parse(input)?;
+    Some(disk.compact_blocks().checksum())
+}
 
-            let rest = identified_entries[target_idx].entry.skip.checked_sub(new_mover.entry.blocks).expect("rest shouldn't underflow");
+pub fn part_two(input: &str) -> Option<u64> {
+    let disk = Disk::parse(input)?;
+    Some(disk.compact_files().checksum())
+}
 
-            identified_entries[target_idx].entry.skip = 0;
-            new_mover.entry.skip = rest;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            identified_entries.remove(mover_idx); // Note: since target is always to the left, we need to remove this one first
+    // Expands the disk into blocks and moves one block at a time
+    fn brute_force_part_one(input: &str) -> u64 {
+        let mut blocks: Vec<Option<usize>> = vec![];
+        for (i, c) in input.trim().chars().enumerate() {
+            let id = if i % 2 == 0 { Some(i / 2) } else { None };
+            blocks.extend(std::iter::repeat_n(id, c.to_digit(10).unwrap() as usize));
+        }
 
-            identified_entries.insert(target_idx + 1, new_mover);
+        let mut l = 0;
+        let mut r = blocks.len().saturating_sub(1);
+        while l < r {
+            if blocks[l].is_some() {
+                l += 1;
+            } else if blocks[r].is_none() {
+                r -= 1;
+            } else {
+                blocks.swap(l, r);
+            }
         }
+
+        blocks
+            .iter()
+            .enumerate()
+            .map(|(pos, id)| (pos * id.unwrap_or(0)) as u64)
+            .sum()
     }
 
-    Some(spread_checksum_part_2(&identified_entries).try_into().expect("Should convert"))
-}
+    #[test]
+    fn test_parse_render() {
+        let disk = Disk::parse("12345").unwrap();
+        assert_eq!(disk.to_string(), "0..111....22222");
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let disk = Disk::parse(&advent_of_code::template::read_file("examples", DAY)).unwrap();
+        assert_eq!(
+            disk.to_string(),
+            "00...111...2...333.44.5555.6666.777.888899"
+        );
+    }
 
     #[test]
-    fn test_parse_spread() {
-        let diskmap = DiskMap::parse("12345").unwrap();
+    fn test_compact_blocks() {
+        let disk = Disk::parse("12345").unwrap().compact_blocks();
+        assert_eq!(disk.to_string(), "022111222......");
+
+        let disk = Disk::parse(&advent_of_code::template::read_file("examples", DAY)).unwrap();
+        let compacted = disk.compact_blocks();
         assert_eq!(
-            vec![
-                Some(0),
-                None,
-                None,
-                Some(1),
-                Some(1),
-                Some(1),
-                None,
-                None,
-                None,
-                None,
-                Some(2),
-                Some(2),
-                Some(2),
-                Some(2),
-                Some(2)
-            ],
-            diskmap.spread()
+            compacted.to_string(),
+            "0099811188827773336446555566.............."
         );
+        assert_eq!(compacted.checksum(), 1928);
     }
 
     #[test]
-    fn test_checksum() {
-        let numbers = vec![
-            0, 0, 9, 9, 8, 1, 1, 1, 8, 8, 8, 2, 7, 7, 7, 3, 3, 3, 6, 4, 4, 6, 5, 5, 5, 5, 6, 6,
-        ];
-        assert_eq!(1928, checksum(numbers))
+    fn test_compact_files() {
+        let disk = Disk::parse(&advent_of_code::template::read_file("examples", DAY)).unwrap();
+        let compacted = disk.compact_files();
+        assert_eq!(
+            compacted.to_string(),
+            "00992111777.44.333....5555.6666.....8888.."
+        );
+        assert_eq!(compacted.checksum(), 2858);
     }
 
     #[test]
@@ -269,7 +260,21 @@ mod tests {
     #[test]
     fn test_part_two() {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        // assert_eq!(result, Some(2858));
-        assert_eq!(result, None);
+        assert_eq!(result, Some(2858));
+    }
+
+    #[test]
+    fn test_part_one_matches_brute_force() {
+        // Includes empty files and free spans, and ids with several digits
+        let input = (0..301)
+            .map(|i| ((i * 7 + i / 3) % 10).to_string())
+            .collect::<String>();
+        assert_eq!(part_one(&input), Some(brute_force_part_one(&input)));
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert_eq!(part_one("12x45"), None);
+        assert_eq!(part_two("12x45"), None);
     }
 }
@@ -1,164 +1,208 @@
-use std::collections::{HashMap, HashSet};
-
-use itertools::Itertools;
+use advent_of_code::{
+    graph::BitSet,
+    regions::{neighbors, Connectivity, Coordinate},
+};
 
 advent_of_code::solution!(10);
 
-type Coordinate = (i32, i32);
-type Terrain = HashMap<Coordinate, i32>;
-
-fn parse(s: &str) -> Option<Terrain> {
-    let terrain: Terrain = s
-        .lines()
-        .enumerate()
-        .flat_map(|(row, line)| {
-            let out = line.char_indices().map(move |(col, tile)| {
-                if let Some(height) = tile.to_string().parse::<u32>().ok() {
-                    Some(((row as i32, col as i32), height as i32))
-                } else {
-                    None
-                }
-            });
-            out
-        })
-        .collect::<Option<Terrain>>()?;
+// Impassable, as in some of the smaller examples in the puzzle text
+const IMPASSABLE: char = '.';
 
-    Some(terrain)
+// Where trails start and end, and how much the height changes with every step
+#[derive(Clone, Copy, Debug)]
+struct TrailRule {
+    trailhead: u32,
+    peak: u32,
+    delta: i32,
 }
 
-fn is_within_bounds(coordinate: &Coordinate, rows: i32, cols: i32) -> bool {
-    return (0 <= coordinate.0)
-        && (coordinate.0 < rows)
-        && (0 <= coordinate.1)
-        && (coordinate.1 < cols);
+const HIKING_TRAIL: TrailRule = TrailRule {
+    trailhead: 0,
+    peak: 9,
+    delta: 1,
+};
+
+// A trailhead with the number of peaks it leads to, and the number of distinct trails
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Trailhead {
+    coord: Coordinate,
+    score: usize,
+    rating: u64,
 }
 
-fn neighbor_coordinates(coordinate: &Coordinate, rows: i32, cols: i32) -> Vec<Coordinate> {
-    vec![
-        (coordinate.0 + 1, coordinate.1),
-        (coordinate.0 - 1, coordinate.1),
-        (coordinate.0, coordinate.1 + 1),
-        (coordinate.0, coordinate.1 - 1),
-    ]
-    .into_iter()
-    .filter(|c| is_within_bounds(coordinate, rows, cols))
-    .collect()
+struct TopographicMap {
+    // Row-major, `None` for impassable tiles
+    heights: Vec<Option<u32>>,
+    rows: usize,
+    cols: usize,
 }
 
-fn neighbors(
-    terrain: &Terrain,
-    coordinate: &Coordinate,
-    rows: i32,
-    cols: i32,
-) -> Vec<(Coordinate, i32)> {
-    neighbor_coordinates(coordinate, rows, cols)
-        .into_iter()
-        .map(|coordinate| {
-            if let Some(height) = terrain.get(&coordinate) {
-                Some((coordinate, *height))
-            } else {
-                None
-            }
-        })
-        .filter_map(|x| x)
-        .collect()
-}
+impl TopographicMap {
+    fn parse(s: &str) -> Option<TopographicMap> {
+        let rows = s.lines().count();
+        let cols = s.lines().next()?.len();
+        let mut heights = Vec::with_capacity(rows * cols);
 
-fn reachable_tops(
-    terrain: &Terrain,
-    coordinate: &Coordinate,
-    rows: i32,
-    cols: i32,
-) -> HashSet<Coordinate> {
-    let height = terrain.get(coordinate).unwrap();
+        for line in s.lines() {
+            if line.len() != cols {
+                return None;
+            }
+            for tile in line.chars() {
+                let height = match tile {
+                    IMPASSABLE => None,
+                    tile => Some(tile.to_digit(10)?),
+                };
+                heights.push(height);
+            }
+        }
 
-    if *height == 9 {
-        return vec![coordinate.clone()].into_iter().collect::<HashSet<_>>();
+        Some(TopographicMap {
+            heights,
+            rows,
+            cols,
+        })
     }
 
-    let neighbor_tiles = neighbors(terrain, coordinate, rows, cols);
-
-    let possible_next_tiles = neighbor_tiles
-        .iter()
-        .filter(|(_, neigh_height)| *neigh_height == (height + 1));
-
-    let reachable: HashSet<Coordinate> = possible_next_tiles
-        .flat_map(|(neigh_coord, _)| reachable_tops(terrain, neigh_coord, rows, cols))
-        .collect();
-
-    reachable
-}
-
-fn reachable_top_paths(terrain: &Terrain, coordinate: &Coordinate, rows: i32, cols: i32) -> u32 {
-    let height = terrain.get(coordinate).unwrap();
+    fn height(&self, coord: Coordinate) -> Option<u32> {
+        self.heights[coord.0 * self.cols + coord.1]
+    }
 
-    if *height == 9 {
-        return 1;
+    fn coordinates(&self) -> impl Iterator<Item = Coordinate> + '_ {
+        (0..self.rows).flat_map(move |row| (0..self.cols).map(move |col| (row, col)))
     }
 
-    let neighbor_tiles = neighbors(terrain, coordinate, rows, cols);
+    // The neighbors one step further along a trail
+    fn next_steps(&self, coord: Coordinate, rule: TrailRule) -> impl Iterator<Item = usize> + '_ {
+        let next_height = self
+            .height(coord)
+            .and_then(|height| height.checked_add_signed(rule.delta));
+        neighbors(coord, self.rows, self.cols, Connectivity::Four)
+            .filter(move |neighbor| next_height.is_some() && self.height(*neighbor) == next_height)
+            .map(|neighbor| neighbor.0 * self.cols + neighbor.1)
+    }
 
-    let possible_next_tiles = neighbor_tiles
-        .iter()
-        .filter(|(_, neigh_height)| *neigh_height == (height + 1));
+    // Every step goes the same way in height, so the trails form a DAG. Sweeping the tiles from
+    // the peaks backwards, every tile is done before the ones that step onto it, which then just
+    // combine the peaks and trail counts of their next steps.
+    fn trailheads(&self, rule: TrailRule) -> Option<Vec<Trailhead>> {
+        if rule.delta == 0 {
+            return None;
+        }
+
+        let mut order = self
+            .coordinates()
+            .filter(|coord| self.height(*coord).is_some())
+            .collect::<Vec<_>>();
+        order.sort_by_key(|coord| self.height(*coord));
+        if rule.delta > 0 {
+            order.reverse();
+        }
+
+        let mut peaks = vec![BitSet::new(); self.heights.len()];
+        let mut trails = vec![0u64; self.heights.len()];
+        let mut peak_count = 0;
+        for coord in order {
+            let index = coord.0 * self.cols + coord.1;
+            if self.height(coord) == Some(rule.peak) {
+                peaks[index].insert(peak_count);
+                peak_count += 1;
+                trails[index] = 1;
+                continue;
+            }
 
-    let score = possible_next_tiles
-        .map(|(neigh_coord, _)| reachable_top_paths(terrain, neigh_coord, rows, cols))
-        .sum();
-    score
+            let mut reachable = BitSet::new();
+            for next in self.next_steps(coord, rule) {
+                reachable.union_with(&peaks[next]);
+                trails[index] += trails[next];
+            }
+            peaks[index] = reachable;
+        }
+
+        let trailheads = self
+            .coordinates()
+            .filter(|coord| self.height(*coord) == Some(rule.trailhead))
+            .map(|coord| {
+                let index = coord.0 * self.cols + coord.1;
+                Trailhead {
+                    coord,
+                    score: peaks[index].len(),
+                    rating: trails[index],
+                }
+            })
+            .collect();
+        Some(trailheads)
+    }
 }
 
 pub fn part_one(input: &str) -> Option<u32> {
-    let terrain: Terrain = parse(input)?;
-
-    let rows: i32 = input.lines().collect_vec().len().try_into().ok()?;
-    let cols: i32 = input.lines().nth(0).map(|x| x.len())?.try_into().ok()?;
-
-    let start_positions: Vec<Coordinate> = terrain
-        .clone()
-        .into_iter()
-        .filter(|(_, height)| *height == 0)
-        .map(|(coord, _)| coord)
-        .collect();
-
-    let total_score = start_positions
-        .iter()
-        .map(|start_coord| {
-            let reachable = reachable_tops(&terrain, start_coord, rows, cols);
-            let count = reachable.len();
-            count as u32
-        }).sum();
-
-    Some(total_score)
+    let map = TopographicMap::parse(input)?;
+    let trailheads = map.trailheads(HIKING_TRAIL)?;
+    let total_score: usize = trailheads.iter().map(|trailhead| trailhead.score).sum();
+    total_score.try_into().ok()
 }
 
 pub fn part_two(input: &str) -> Option<u32> {
-    let terrain: Terrain = parse(input)?;
-
-    let rows: i32 = input.lines().collect_vec().len().try_into().ok()?;
-    let cols: i32 = input.lines().nth(0).map(|x| x.len())?.try_into().ok()?;
-
-    let start_positions: Vec<Coordinate> = terrain
-        .clone()
-        .into_iter()
-        .filter(|(_, height)| *height == 0)
-        .map(|(coord, _)| coord)
-        .collect();
-
-    let total_score = start_positions
-        .iter()
-        .map(|start_coord| {
-            let reachable_paths = reachable_top_paths(&terrain, start_coord, rows, cols);
-            reachable_paths as u32
-        }).sum();
-
-    Some(total_score)
+    let map = TopographicMap::parse(input)?;
+    let trailheads = map.trailheads(HIKING_TRAIL)?;
+    let total_rating: u64 = trailheads.iter().map(|trailhead| trailhead.rating).sum();
+    total_rating.try_into().ok()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_trailheads() {
+        let map =
+            TopographicMap::parse(&advent_of_code::template::read_file("examples", DAY)).unwrap();
+        let trailheads = map.trailheads(HIKING_TRAIL).unwrap();
+        assert_eq!(
+            trailheads.iter().map(|t| t.score).collect::<Vec<_>>(),
+            vec![5, 6, 5, 3, 1, 3, 5, 3, 5]
+        );
+        assert_eq!(
+            trailheads.iter().map(|t| t.rating).collect::<Vec<_>>(),
+            vec![20, 24, 10, 4, 1, 4, 5, 8, 5]
+        );
+        assert_eq!(trailheads[0].coord, (0, 2));
+    }
+
+    #[test]
+    fn test_impassable_tiles() {
+        let map =
+            TopographicMap::parse("...0...\n...1...\n...2...\n6543456\n7.....7\n8.....8\n9.....9")
+                .unwrap();
+        let trailheads = map.trailheads(HIKING_TRAIL).unwrap();
+        assert_eq!(trailheads.len(), 1);
+        assert_eq!(trailheads[0].score, 2);
+        assert_eq!(trailheads[0].rating, 2);
+    }
+
+    #[test]
+    fn test_custom_rule() {
+        // Walking down from the peaks, two at a time
+        let map = TopographicMap::parse("8642\n6...\n4...\n2...").unwrap();
+        let rule = TrailRule {
+            trailhead: 8,
+            peak: 2,
+            delta: -2,
+        };
+        let trailheads = map.trailheads(rule).unwrap();
+        assert_eq!(trailheads.len(), 1);
+        assert_eq!(trailheads[0].score, 2);
+        assert_eq!(trailheads[0].rating, 2);
+
+        let flat = TrailRule { delta: 0, ..rule };
+        assert_eq!(map.trailheads(flat), None);
+    }
+
+    #[test]
+    fn test_invalid_map() {
+        assert!(TopographicMap::parse("012\n34").is_none());
+        assert!(TopographicMap::parse("0x2").is_none());
+    }
+
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
@@ -168,6 +212,6 @@ mod tests {
     #[test]
     fn test_part_two() {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, None);
+        assert_eq!(result, Some(81));
     }
 }
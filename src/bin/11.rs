@@ -1,6 +1,6 @@
-use memoize::memoize;
+use std::collections::HashMap;
 
-advent_of_code::solution!(11);
+advent_of_code::solution!(11, debug = debug);
 
 fn number_of_digits(stone: u64) -> usize {
     (stone.checked_ilog10().unwrap_or(0) + 1) as usize
@@ -13,7 +13,7 @@ fn split_stone(stone: u64) -> Vec<u64> {
     let first = stone / x;
     let second = stone % x;
 
-    return vec![first, second];
+    vec![first, second]
 }
 
 // Every time you blink, the stones each simultaneously change according to the first applicable rule in this list:
@@ -28,51 +28,100 @@ fn blink(stone: u64) -> Vec<u64> {
         return vec![1];
     }
 
-    if (number_of_digits(stone) % 2) == 0 {
+    if number_of_digits(stone).is_multiple_of(2) {
         return split_stone(stone);
     }
 
-    return vec![stone * 2024];
+    vec![stone * 2024]
 }
 
-#[memoize]
-fn stones_after_blinking_n_times(stone: u64, times: u32) -> u64 {
-    if times == 0 {
-        return 1;
-    }
+// How many stones have each number. The order of the stones never matters, and most numbers
+// show up many times, so this stays small even when the number of stones doesn't.
+#[derive(Clone, Debug, PartialEq)]
+struct Stones {
+    counts: HashMap<u64, u64>,
+}
 
-    blink(stone)
-        .into_iter()
-        .map(|s| stones_after_blinking_n_times(s, times - 1))
-        .sum()
+// The size of the line of stones after some number of blinks
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Generation {
+    blinks: usize,
+    stones: u64,
+    distinct: usize,
 }
 
-pub fn part_one(input: &str) -> Option<u64> {
-    let initial_stones = input
-        .split(" ")
-        .map(|s| s.parse::<u64>().ok())
-        .collect::<Option<Vec<_>>>()?;
+impl Stones {
+    fn parse(input: &str) -> Option<Stones> {
+        let mut counts = HashMap::new();
+        for stone in input.split_whitespace() {
+            *counts.entry(stone.parse().ok()?).or_default() += 1;
+        }
+        Some(Stones { counts })
+    }
 
-    let final_stone_count: u64 = initial_stones
-        .iter()
-        .map(|stone| stones_after_blinking_n_times(*stone, 25))
-        .sum();
+    fn blink(&self) -> Stones {
+        let mut counts = HashMap::with_capacity(self.counts.len());
+        for (stone, count) in &self.counts {
+            for next in blink(*stone) {
+                *counts.entry(next).or_default() += count;
+            }
+        }
+        Stones { counts }
+    }
+
+    fn blink_times(&self, blinks: usize) -> Stones {
+        (0..blinks).fold(self.clone(), |stones, _| stones.blink())
+    }
 
-    Some(final_stone_count)
+    fn len(&self) -> u64 {
+        self.counts.values().sum()
+    }
+
+    fn distinct(&self) -> usize {
+        self.counts.len()
+    }
+
+    // The generations from the current one up to and including the one after the blinks
+    fn growth_curve(&self, blinks: usize) -> Vec<Generation> {
+        std::iter::successors(Some(self.clone()), |stones| Some(stones.blink()))
+            .take(blinks + 1)
+            .enumerate()
+            .map(|(blinks, stones)| Generation {
+                blinks,
+                stones: stones.len(),
+                distinct: stones.distinct(),
+            })
+            .collect()
+    }
 }
 
-pub fn part_two(input: &str) -> Option<u64> {
-    let initial_stones = input
-        .split(" ")
-        .map(|s| s.parse::<u64>().ok())
-        .collect::<Option<Vec<_>>>()?;
+fn stones_after_blinking(input: &str, blinks: usize) -> Option<u64> {
+    Some(Stones::parse(input)?.blink_times(blinks).len())
+}
+
+// How the number of stones and distinct stones grows up to the blinks of part two
+fn debug(input: &str) -> Option<String> {
+    let curve = Stones::parse(input)?.growth_curve(75);
+    Some(
+        curve
+            .iter()
+            .map(|generation| {
+                format!(
+                    "blink {:>2}: {} stones, {} distinct",
+                    generation.blinks, generation.stones, generation.distinct
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    )
+}
 
-    let final_stone_count: u64 = initial_stones
-        .iter()
-        .map(|stone| stones_after_blinking_n_times(*stone, 75))
-        .sum();
+pub fn part_one(input: &str) -> Option<u64> {
+    stones_after_blinking(input, 25)
+}
 
-    Some(final_stone_count)
+pub fn part_two(input: &str) -> Option<u64> {
+    stones_after_blinking(input, 75)
 }
 
 #[cfg(test)]
@@ -84,15 +133,50 @@ mod tests {
         assert_eq!(vec![12, 34], split_stone(1234));
     }
 
+    #[test]
+    fn test_blink() {
+        let stones = Stones::parse("0 1 10 99 999").unwrap().blink();
+        assert_eq!(stones, Stones::parse("1 2024 1 0 9 9 2021976").unwrap());
+        assert_eq!(stones.len(), 7);
+        assert_eq!(stones.distinct(), 5);
+    }
+
+    #[test]
+    fn test_growth_curve() {
+        let stones = Stones::parse(&advent_of_code::template::read_file("examples", DAY)).unwrap();
+        let curve = stones.growth_curve(6);
+        assert_eq!(
+            curve
+                .iter()
+                .map(|generation| generation.stones)
+                .collect::<Vec<_>>(),
+            vec![2, 3, 4, 5, 9, 13, 22]
+        );
+        assert_eq!(
+            curve[..5]
+                .iter()
+                .map(|generation| generation.distinct)
+                .collect::<Vec<_>>(),
+            vec![2, 3, 4, 5, 8]
+        );
+        assert_eq!(curve[6].blinks, 6);
+        assert_eq!(stones.blink_times(6).len(), 22);
+    }
+
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(55312));
     }
 
-    // #[test]
-    // fn test_part_two() {
-    //     let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-    //     assert_eq!(result, None);
-    // }
+    #[test]
+    fn test_part_two() {
+        let result = part_two(&advent_of_code::template::read_file("examples", DAY));
+        assert_eq!(result, Some(65601038650482));
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert_eq!(part_one("125 x"), None);
+    }
 }
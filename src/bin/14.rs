@@ -1,5 +1,3 @@
-use std::{collections::HashSet, fs};
-
 use itertools::Itertools;
 use lazy_static::lazy_static;
 use regex::Regex;
@@ -10,6 +8,9 @@ type Position = (i64, i64);
 type Velocity = (i64, i64);
 type BoardSize = (i64, i64);
 
+// Rows and columns of the space the robots move in, for the real input
+const BOARD_SIZE: BoardSize = (103, 101);
+
 #[derive(Debug, PartialEq)]
 struct Robot {
     pos: Position,
@@ -37,9 +38,9 @@ fn simulate_single_dim(initial: i64, vel: i64, steps: i64, size: i64) -> i64 {
     let unwrapped_pos = initial + translation;
     let wrapped = unwrapped_pos % size;
     if wrapped >= 0 {
-        return wrapped;
+        wrapped
     } else {
-        return size + wrapped;
+        size + wrapped
     }
 }
 
@@ -50,18 +51,11 @@ fn simulate(initial_pos: Position, vel: Velocity, steps: i64, rows: i64, cols: i
     (row, col)
 }
 
-pub fn part_one(input: &str) -> Option<usize> {
-    let robots = input
-        .lines()
-        .map(|s| Robot::new(s))
-        .collect::<Option<Vec<_>>>()?;
-
-    let board_size: BoardSize = if robots.len() == 500 {
-        (103, 101) // Input
-    } else {
-        (7, 11) // Example
-    };
+fn parse_robots(input: &str) -> Option<Vec<Robot>> {
+    input.lines().map(Robot::new).collect()
+}
 
+fn safety_factor(robots: &[Robot], board_size: BoardSize) -> Option<usize> {
     let steps = 100;
 
     let final_positions: Vec<Position> = robots
@@ -69,8 +63,6 @@ pub fn part_one(input: &str) -> Option<usize> {
         .map(|robot| simulate(robot.pos, robot.vel, steps, board_size.0, board_size.1))
         .collect();
 
-    println!("Final positions: {:?}", final_positions);
-
     let middle_row = board_size.0 / 2;
     let middle_col = board_size.1 / 2;
 
@@ -80,8 +72,6 @@ pub fn part_one(input: &str) -> Option<usize> {
         .filter(|pos| pos.0 != middle_row)
         .filter(|pos| pos.1 != middle_col);
 
-    println!("Filtered: {:?}", filtered);
-
     let robots_per_quadrant = filtered
         .into_grouping_map_by(|pos| ((pos.0 > middle_row), (pos.1 > middle_col)))
         .fold(0, |acc, _, _| acc + 1)
@@ -89,63 +79,74 @@ pub fn part_one(input: &str) -> Option<usize> {
         .copied()
         .collect::<Vec<usize>>();
 
-    println!("Robots per quadrant: {:?}", robots_per_quadrant);
-
-    let safety_score = robots_per_quadrant.into_iter().reduce(|acc, e| acc * e)?;
+    robots_per_quadrant.into_iter().reduce(|acc, e| acc * e)
+}
 
-    Some(safety_score)
-    // None
+pub fn part_one(input: &str) -> Option<usize> {
+    let robots = parse_robots(input)?;
+    safety_factor(&robots, BOARD_SIZE)
 }
 
-fn board_string(positions: &Vec<Position>, board_size: BoardSize) -> String {
-    let occupied: HashSet<Position> = positions.clone().into_iter().collect();
-
-    let mut out: String = String::new();
-    for row in 0..board_size.0 {
-        for col in 0..board_size.1 {
-            if occupied.contains(&(row, col)) {
-                out.push('#');
-            } else {
-                out.push(' ');
-            }
-        }
-        out.push('\n');
-    }
+// Variance scaled by n^2, which keeps everything in integers and preserves ordering
+fn scaled_variance(values: impl Iterator<Item = i64>) -> i64 {
+    let (n, sum, sum_sq) = values.fold((0, 0, 0), |(n, sum, sum_sq), v| {
+        (n + 1, sum + v, sum_sq + v * v)
+    });
+    n * sum_sq - sum * sum
+}
 
-    out
+// Each dimension repeats with a period of its size, so the step where the robots are
+// most clustered along it only needs to be searched for within one period
+fn least_spread_step(initial: &[(i64, i64)], size: i64) -> i64 {
+    (0..size)
+        .min_by_key(|&step| {
+            scaled_variance(
+                initial
+                    .iter()
+                    .map(|&(pos, vel)| simulate_single_dim(pos, vel, step, size)),
+            )
+        })
+        .unwrap_or(0)
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
-    let robots = input
-        .lines()
-        .map(|s| Robot::new(s))
-        .collect::<Option<Vec<_>>>()?;
+fn mod_inverse(a: i64, modulus: i64) -> Option<i64> {
+    // Extended euclidean algorithm
+    let (mut old_r, mut r) = (a.rem_euclid(modulus), modulus);
+    let (mut old_s, mut s) = (1, 0);
+    while r != 0 {
+        let quotient = old_r / r;
+        (old_r, r) = (r, old_r - quotient * r);
+        (old_s, s) = (s, old_s - quotient * s);
+    }
 
-    let board_size: BoardSize = if robots.len() == 500 {
-        (103, 101) // Input
+    if old_r == 1 {
+        Some(old_s.rem_euclid(modulus))
     } else {
-        (7, 11) // Example
-    };
-
-    // let out_file_name = "data/outputs/day14.txt";
+        None
+    }
+}
 
-    for step in 5000..10000 {
-        let final_positions: Vec<Position> = robots
-            .iter()
-            .map(|robot| simulate(robot.pos, robot.vel, step, board_size.0, board_size.1))
-            .collect();
+// Finds the smallest non-negative step t with t = row_step (mod rows) and t = col_step (mod cols)
+fn chinese_remainder(row_step: i64, rows: i64, col_step: i64, cols: i64) -> Option<i64> {
+    let inverse = mod_inverse(rows, cols)?;
+    let k = ((col_step - row_step) * inverse).rem_euclid(cols);
+    Some(row_step + rows * k)
+}
 
-        let readable_board = board_string(&final_positions, board_size);
+fn easter_egg_step(robots: &[Robot], (rows, cols): BoardSize) -> Option<i64> {
+    // The easter egg is the step where the robots are the most tightly packed in both dimensions
+    let row_dims = robots.iter().map(|r| (r.pos.0, r.vel.0)).collect_vec();
+    let col_dims = robots.iter().map(|r| (r.pos.1, r.vel.1)).collect_vec();
 
-        println!("Step {}", step);
-        println!("{}", readable_board);
+    let row_step = least_spread_step(&row_dims, rows);
+    let col_step = least_spread_step(&col_dims, cols);
 
-        // fs::write(out_file_name, format!("Step {}", step)).ok()?;
-        // fs::write(out_file_name, readable_board).ok()?;
-        // fs::write(out_file_name, "\n").ok()?;
-    }
+    chinese_remainder(row_step, rows, col_step, cols)
+}
 
-    None
+pub fn part_two(input: &str) -> Option<i64> {
+    let robots = parse_robots(input)?;
+    easter_egg_step(&robots, BOARD_SIZE)
 }
 
 #[cfg(test)]
@@ -174,22 +175,46 @@ mod tests {
     #[test]
     fn test_parse_all() {
         let input = advent_of_code::template::read_file("examples", DAY);
-        input
-            .lines()
-            .map(|s| Robot::new(s))
-            .collect::<Option<Vec<_>>>()
-            .unwrap();
+        parse_robots(&input).unwrap();
     }
 
     #[test]
     fn test_part_one() {
-        let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(12));
+        // The example robots move in a smaller space than the real ones
+        let robots = parse_robots(&advent_of_code::template::read_file("examples", DAY)).unwrap();
+        assert_eq!(safety_factor(&robots, (7, 11)), Some(12));
+    }
+
+    #[test]
+    fn test_chinese_remainder() {
+        assert_eq!(chinese_remainder(3, 7, 5, 11), Some(38));
+        assert_eq!(chinese_remainder(0, 103, 0, 101), Some(0));
+        assert_eq!(chinese_remainder(1, 4, 1, 6), None);
     }
 
     #[test]
-    fn test_part_two() {
-        let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, None);
+    fn test_part_two_clustered() {
+        // 500 robots that all land inside a small square after 6532 steps
+        let (rows, cols) = BOARD_SIZE;
+        let steps = 6532;
+        let mut seed: i64 = 17;
+        let mut next = |modulus: i64| {
+            seed = (seed * 1103515245 + 12345) % (1 << 31);
+            seed % modulus
+        };
+
+        let input = (0..500)
+            .map(|_| {
+                let target = (40 + next(20), 30 + next(20));
+                let vel = (next(201) - 100, next(201) - 100);
+                let pos = (
+                    simulate_single_dim(target.0, -vel.0, steps, rows),
+                    simulate_single_dim(target.1, -vel.1, steps, cols),
+                );
+                format!("p={},{} v={},{}", pos.1, pos.0, vel.1, vel.0)
+            })
+            .join("\n");
+
+        assert_eq!(part_two(&input), Some(steps));
     }
 }
@@ -1,70 +1,18 @@
-use itertools::Itertools;
+use std::{collections::HashSet, fmt};
 
 advent_of_code::solution!(15);
 
 type Coordinate = (usize, usize);
 
-struct Board {
-    tiles: Vec<Vec<char>>,
-    rows: usize,
-    cols: usize,
-}
-
-impl Board {
-    fn new(s: &str) -> Option<Board> {
-        let tiles: Vec<Vec<char>> = s.lines().map(|line| line.chars().collect()).collect();
-
-        let rows = s.lines().collect_vec().len();
-        let cols = s.lines().nth(0).map(|x| x.len())?;
-
-        Some(Board { tiles, rows, cols })
-    }
-
-    fn get(&self, coordinate: &Coordinate) -> char {
-        self.tiles[coordinate.0][coordinate.1]
-    }
-
-    fn set(&mut self, coordinate: &Coordinate, tile: char) {
-        self.tiles[coordinate.0][coordinate.1] = tile
-    }
-
-    fn swap(&mut self, first: &Coordinate, second: &Coordinate) {
-        let temp = self.get(first);
-        self.set(first, self.get(second));
-        self.set(second, temp)
-    }
-
-    fn as_string(&self) -> String {
-        let mut out: String = String::new();
-        for row in 0..self.rows {
-            for col in 0..self.cols {
-                out.push(self.get(&(row, col)))
-            }
-            out.push('\n');
-        }
-
-        out
-    }
-}
-
-/** Preprocess the string.
-If the tile is #, the new map contains ## instead.
-If the tile is O, the new map contains [] instead.
-If the tile is ., the new map contains .. instead.
-If the tile is @, the new map contains @. instead.
- */
-fn preprocess(s: &str) -> String {
-    s.chars()
-        .map(|c| match c {
-            '#' => "##",
-            'O' => "[]",
-            '.' => "..",
-            '@' => "@.",
-            '\n' => "\n",
-            _ => panic!("Unexpected char in preprocess!"),
-        })
-        .collect()
-}
+// Possible tiles
+const WALL: char = '#';
+const EMPTY: char = '.';
+const ROBOT: char = '@';
+const BOX: char = 'O';
+// Wider boxes are drawn as [], [-], [--], ...
+const BOX_LEFT: char = '[';
+const BOX_MIDDLE: char = '-';
+const BOX_RIGHT: char = ']';
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 enum Direction {
@@ -84,6 +32,16 @@ impl Direction {
             _ => None,
         }
     }
+
+    #[cfg(test)]
+    fn opposite(&self) -> Direction {
+        match self {
+            Direction::North => Direction::South,
+            Direction::East => Direction::West,
+            Direction::South => Direction::North,
+            Direction::West => Direction::East,
+        }
+    }
 }
 
 // Coordinates in format (y, x)
@@ -96,311 +54,305 @@ fn coordinate_in_direction(coord: Coordinate, direction: Direction) -> Option<Co
     }
 }
 
-/*
-How to simulate a step:
-- Find (or know) the position of the robot ('@')
-- Parse the direction
-- Iterate in that direction
-    a: It has a wall ('#')
-        -> Do nothing, done
-    b: It is just air ('.')
-        -> Move the agent there (swap agent and air chars)
-    c: It has a box ('O')
-        -> Check the next in that direction
-            - It is still a box -> Keep going
-            - It is a wall -> No move can be made, do nothing, done
-            - It is air -> Want to move all the box.
-                But this is a bit cumbersome, so it is better to
-                1. Swap the box and the air
-                2. Swap the air and the robot
- */
+#[derive(Debug, PartialEq)]
+enum WarehouseError {
+    UnexpectedTile { tile: char, coord: Coordinate },
+    UnclosedBox(Coordinate),
+    MissingRobot,
+    MultipleRobots,
+    MissingMoves,
+    UnknownMove(char),
+    OutOfBounds(Coordinate),
+}
 
-fn first_non_box_in_direction(
-    board: &Board,
-    coord: Coordinate,
-    direction: Direction,
-) -> (Coordinate, char) {
-    let next_coord =
-        coordinate_in_direction(coord, direction).expect("Shouldn't get out of bounds!");
-    let next_tile = board.get(&next_coord);
-    match next_tile {
-        '.' | '#' => (next_coord, next_tile),
-        'O' => first_non_box_in_direction(board, next_coord, direction),
-        _ => panic!(
-            "Finding first non-boulder - unexpected tile {} at coordinate {:?}",
-            next_tile, next_coord
-        ),
-    }
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Tile {
+    Wall,
+    Empty,
+    // Index into the boxes of the warehouse
+    Box(usize),
 }
 
-// Returns new agent pos
-fn step(board: &mut Board, agent_pos: Coordinate, direction: Direction) -> Coordinate {
-    if let Some(front_coord) = coordinate_in_direction(agent_pos, direction) {
-        match board.get(&front_coord) {
-            '#' => return agent_pos, // Do nothing
-            '.' => {
-                // Move the agent to the empty air
-                board.swap(&agent_pos, &front_coord);
-                return front_coord;
-            }
-            'O' => {
-                let (end_coord, end_tile) =
-                    first_non_box_in_direction(board, front_coord, direction);
-                if end_tile == '.' {
-                    board.swap(&front_coord, &end_coord); // Swap box and air
-                    board.swap(&agent_pos, &front_coord); // Swap agent and air
-                    return front_coord;
-                } else if end_tile == '#' {
-                    return agent_pos; // Do nothing
-                } else {
-                    panic!("Unexpected end tile.")
-                }
-            }
-            '@' => panic!("Unexpected agent!"),
-            _ => panic!("Unknown tile!"),
-        }
+// A box covering `width` tiles to the east of its left coordinate
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct WarehouseBox {
+    left: Coordinate,
+    width: usize,
+}
+
+impl WarehouseBox {
+    fn coords(&self) -> impl Iterator<Item = Coordinate> {
+        let left = self.left;
+        (0..self.width).map(move |offset| (left.0, left.1 + offset))
     }
+}
+
+// One move of the robot, with what it takes to undo it. Only recorded in tests, where undoing
+// moves checks the engine against the intermediate states of the examples.
+#[cfg(test)]
+#[derive(Clone, Debug, PartialEq)]
+struct Step {
+    direction: Direction,
+    // The boxes pushed, or None if the robot couldn't move
+    pushed: Option<Vec<usize>>,
+}
 
-    panic!("Couldn't find coord in direction!");
+struct Warehouse {
+    tiles: Vec<Vec<Tile>>,
+    boxes: Vec<WarehouseBox>,
+    robot: Coordinate,
+    #[cfg(test)]
+    history: Vec<Step>,
 }
 
-// (y, x)
-fn find_agent_or_fail(board: &Board) -> (usize, usize) {
-    for (i, row) in board.tiles.iter().enumerate() {
-        for (j, col) in row.iter().enumerate() {
-            if col == &'@' {
-                return (i, j);
+impl Warehouse {
+    fn parse(s: &str) -> Result<Warehouse, WarehouseError> {
+        let mut tiles = vec![];
+        let mut boxes = vec![];
+        let mut robot = None;
+
+        for (row, line) in s.lines().enumerate() {
+            let mut row_tiles = vec![];
+            let mut open_box: Option<WarehouseBox> = None;
+            for (col, tile) in line.chars().enumerate() {
+                let coord = (row, col);
+                let parsed = match (tile, open_box.as_mut()) {
+                    (BOX_MIDDLE, Some(open)) => {
+                        open.width += 1;
+                        Tile::Box(boxes.len())
+                    }
+                    (BOX_RIGHT, Some(open)) => {
+                        open.width += 1;
+                        boxes.push(*open);
+                        open_box = None;
+                        Tile::Box(boxes.len() - 1)
+                    }
+                    (_, Some(open)) => return Err(WarehouseError::UnclosedBox(open.left)),
+                    (WALL, None) => Tile::Wall,
+                    (EMPTY, None) => Tile::Empty,
+                    (ROBOT, None) => {
+                        if robot.replace(coord).is_some() {
+                            return Err(WarehouseError::MultipleRobots);
+                        }
+                        Tile::Empty
+                    }
+                    (BOX, None) => {
+                        boxes.push(WarehouseBox {
+                            left: coord,
+                            width: 1,
+                        });
+                        Tile::Box(boxes.len() - 1)
+                    }
+                    (BOX_LEFT, None) => {
+                        open_box = Some(WarehouseBox {
+                            left: coord,
+                            width: 1,
+                        });
+                        Tile::Box(boxes.len())
+                    }
+                    (tile, None) => return Err(WarehouseError::UnexpectedTile { tile, coord }),
+                };
+                row_tiles.push(parsed);
+            }
+            if let Some(open) = open_box {
+                return Err(WarehouseError::UnclosedBox(open.left));
             }
+            tiles.push(row_tiles);
         }
-    }
 
-    panic!("Failed to find agent")
-}
-
-pub fn part_one(input: &str) -> Option<u32> {
-    let mut board = Board::new(input.split("\n\n").nth(0)?)?;
-    let directions = input.split("\n\n").nth(1)?;
+        Ok(Warehouse {
+            tiles,
+            boxes,
+            robot: robot.ok_or(WarehouseError::MissingRobot)?,
+            #[cfg(test)]
+            history: vec![],
+        })
+    }
 
-    let mut agent_pos = find_agent_or_fail(&board);
+    fn get(&self, coord: Coordinate) -> Option<Tile> {
+        self.tiles.get(coord.0)?.get(coord.1).copied()
+    }
 
-    for direction_char in directions.lines().flat_map(|line| line.chars()) {
-        // println!("Direction char: {}, agent_pos: {:?}", direction_char, agent_pos);
-        // board.print();
-        let direction = Direction::new(direction_char).expect("Should parse direction");
-        agent_pos = step(&mut board, agent_pos, direction);
+    fn neighbor(
+        &self,
+        coord: Coordinate,
+        direction: Direction,
+    ) -> Result<Coordinate, WarehouseError> {
+        coordinate_in_direction(coord, direction)
+            .filter(|next| self.get(*next).is_some())
+            .ok_or(WarehouseError::OutOfBounds(coord))
     }
 
-    // Find sum of "GPS-coordinates"
-    let gps_coordinate_sum: usize =
-        board
-            .tiles
-            .iter()
-            .enumerate()
-            .flat_map(|(row, row_tiles)| {
-                row_tiles.into_iter().enumerate().map(move |(col, char)| {
-                    if *char == 'O' {
-                        (100 * row) + col
-                    } else {
-                        0
+    // The boxes the robot would push by moving in the direction, or None if they're stuck
+    fn boxes_to_push(&self, direction: Direction) -> Result<Option<Vec<usize>>, WarehouseError> {
+        let mut pushed: Vec<usize> = vec![];
+        let mut seen: HashSet<usize> = HashSet::new();
+        let mut frontier = vec![self.neighbor(self.robot, direction)?];
+
+        while let Some(coord) = frontier.pop() {
+            match self.get(coord) {
+                Some(Tile::Wall) => return Ok(None),
+                Some(Tile::Box(id)) if seen.insert(id) => {
+                    pushed.push(id);
+                    for box_coord in self.boxes[id].coords() {
+                        frontier.push(self.neighbor(box_coord, direction)?);
                     }
-                })
-            })
-            .sum();
-
-    Some(gps_coordinate_sum.try_into().expect("Should convert"))
-}
+                }
+                _ => {}
+            }
+        }
 
-/**
-TODO:
+        Ok(Some(pushed))
+    }
 
-Preprocess the board - DONE
+    fn shift_boxes(&mut self, ids: &[usize], direction: Direction) {
+        for id in ids {
+            for coord in self.boxes[*id].coords() {
+                self.tiles[coord.0][coord.1] = Tile::Empty;
+            }
+        }
+        for id in ids {
+            let warehouse_box = &mut self.boxes[*id];
+            warehouse_box.left = coordinate_in_direction(warehouse_box.left, direction)
+                .expect("Boxes are only shifted to where they've been checked to fit");
+            for coord in warehouse_box.coords() {
+                self.tiles[coord.0][coord.1] = Tile::Box(*id);
+            }
+        }
+    }
 
-A box is pushable if in a direction if the coordinates it will be pushed to
-- Are air
-- Are pushable boxes
+    // Tries to move the robot, pushing any boxes in front of it. Returns whether it moved.
+    fn step(&mut self, direction: Direction) -> Result<bool, WarehouseError> {
+        let pushed = self.boxes_to_push(direction)?;
+        let moved = pushed.is_some();
+        if let Some(pushed) = &pushed {
+            self.shift_boxes(pushed, direction);
+            self.robot = self.neighbor(self.robot, direction)?;
+        }
 
-Before we can execute a push, we need
-- Left coordinate of all boxes (use this to calculate right coord)
-- Direction
+        #[cfg(test)]
+        self.history.push(Step { direction, pushed });
+        Ok(moved)
+    }
 
-Executing a push:
-- Copy all the left-hand-side box coordinates
-- Also, find all the rhs coordinates
-- Move the coordinates in direction and write
-*/
+    fn run(&mut self, moves: &[Direction]) -> Result<(), WarehouseError> {
+        for direction in moves {
+            self.step(*direction)?;
+        }
+        Ok(())
+    }
 
-fn get_rhs_coord(lhs_coord: Coordinate) -> Coordinate {
-    coordinate_in_direction(lhs_coord, Direction::East).expect("Should find coordinate to the east")
-}
+    // Reverts the last step. Returns whether there was a step to revert.
+    #[cfg(test)]
+    fn undo(&mut self) -> Result<bool, WarehouseError> {
+        let Some(step) = self.history.pop() else {
+            return Ok(false);
+        };
+        if let Some(pushed) = step.pushed {
+            let back = step.direction.opposite();
+            self.robot = self.neighbor(self.robot, back)?;
+            self.shift_boxes(&pushed, back);
+        }
+        Ok(true)
+    }
 
-fn get_lhs_coord(rhs_coord: Coordinate) -> Coordinate {
-    coordinate_in_direction(rhs_coord, Direction::West).expect("Should find coordinate to the west")
+    // Sum of the "GPS-coordinates" of the boxes, measured from their left edge
+    fn gps_coordinate_sum(&self) -> usize {
+        self.boxes
+            .iter()
+            .map(|warehouse_box| 100 * warehouse_box.left.0 + warehouse_box.left.1)
+            .sum()
+    }
 }
 
-fn pushable_boxes_in_direction(
-    board: &Board,
-    lhs_coord: Coordinate,
-    direction: Direction,
-) -> (Vec<Coordinate>, bool) {
-    let rhs_coord = get_rhs_coord(lhs_coord);
-    let next_coords = match direction {
-        Direction::East => {
-            vec![coordinate_in_direction(rhs_coord, direction).unwrap()]
-        }
-        Direction::West => {
-            vec![coordinate_in_direction(lhs_coord, direction).unwrap()]
-        }
-        Direction::North | Direction::South => {
-            vec![
-                coordinate_in_direction(lhs_coord, direction).unwrap(),
-                coordinate_in_direction(rhs_coord, direction).unwrap(),
-            ]
-        }
-    };
-
-    let mut pushable_boxes = vec![lhs_coord, rhs_coord];
-    for next_coord in next_coords {
-        let next_tile = board.get(&next_coord);
-        match next_tile {
-            '[' => {
-                let (boxes, pushable) = pushable_boxes_in_direction(board, next_coord, direction);
-                if !pushable {
-                    return (vec![], false)
-                }
-                for b in boxes {
-                    pushable_boxes.push(b);
-                }
-            },
-            ']' => {
-                let next_lhs_coord = get_lhs_coord(next_coord);
-                let (boxes, pushable) = pushable_boxes_in_direction(board, next_lhs_coord, direction);
-                if !pushable {
-                    return (vec![], false)
-                }
-                for b in boxes {
-                    pushable_boxes.push(b);
-                }
-            },
-            '.' => {
-                // Do nothing
-            },
-            '#' => {
-                // Wall. No pushable boxes in direction
-                return (vec![], false)
-            },
-            _ => panic!("Unexpected tile when finding pushable boxes")
+impl fmt::Display for Warehouse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (row, row_tiles) in self.tiles.iter().enumerate() {
+            for (col, tile) in row_tiles.iter().enumerate() {
+                let c = match tile {
+                    _ if self.robot == (row, col) => ROBOT,
+                    Tile::Wall => WALL,
+                    Tile::Empty => EMPTY,
+                    Tile::Box(id) => {
+                        let warehouse_box = self.boxes[*id];
+                        match col - warehouse_box.left.1 {
+                            _ if warehouse_box.width == 1 => BOX,
+                            0 => BOX_LEFT,
+                            offset if offset == warehouse_box.width - 1 => BOX_RIGHT,
+                            _ => BOX_MIDDLE,
+                        }
+                    }
+                };
+                write!(f, "{}", c)?;
+            }
+            writeln!(f)?;
         }
+        Ok(())
     }
-
-    (pushable_boxes, true)
 }
 
-// Returns new agent pos
-fn step_part_2(board: &mut Board, agent_pos: Coordinate, direction: Direction) -> Coordinate {
-    if let Some(front_coord) = coordinate_in_direction(agent_pos, direction) {
-        match board.get(&front_coord) {
-            '#' => return agent_pos, // Do nothing
-            '.' => {
-                // Move the agent to the empty air
-                board.swap(&agent_pos, &front_coord);
-                return front_coord;
-            }
-            '[' => {
-                let did_push = try_pushing_boxes(board, direction, front_coord);
-                if did_push {
-                    board.set(&front_coord, '@');
-                    board.set(&agent_pos, '.');
-                    return front_coord;
-                } else {
-                    return agent_pos;
+/** Scale the warehouse horizontally.
+Every tile becomes `factor` tiles wide. Walls and air are repeated, boxes become boxes of the new
+width, and the robot stays a single tile followed by air.
+ */
+fn widen(s: &str, factor: usize) -> Result<String, WarehouseError> {
+    let mut out = String::new();
+    for (row, line) in s.lines().enumerate() {
+        if row > 0 {
+            out.push('\n');
+        }
+        for (col, tile) in line.chars().enumerate() {
+            match tile {
+                WALL | EMPTY => out.extend(std::iter::repeat_n(tile, factor)),
+                ROBOT => {
+                    out.push(ROBOT);
+                    out.extend(std::iter::repeat_n(EMPTY, factor - 1));
                 }
-            }
-            ']' => {
-                // Same as above, but find the lhs coord first
-                let next_lhs_coord = get_lhs_coord(front_coord);
-                let did_push = try_pushing_boxes(board, direction, next_lhs_coord);
-                if did_push {
-                    board.set(&front_coord, '@');
-                    board.set(&agent_pos, '.');
-                    return front_coord;
-                } else {
-                    return agent_pos;
+                BOX if factor == 1 => out.push(BOX),
+                BOX => {
+                    out.push(BOX_LEFT);
+                    out.extend(std::iter::repeat_n(BOX_MIDDLE, factor - 2));
+                    out.push(BOX_RIGHT);
+                }
+                _ => {
+                    return Err(WarehouseError::UnexpectedTile {
+                        tile,
+                        coord: (row, col),
+                    })
                 }
             }
-            '@' => panic!("Unexpected agent!"),
-            _ => panic!("Unknown tile!"),
         }
     }
-
-    panic!("Couldn't find coord in direction!");
+    Ok(out)
 }
 
-// Returns whether boxes are pushed or not
-fn try_pushing_boxes(board: &mut Board, direction: Direction, next_lhs_coord: (usize, usize)) -> bool {
-    // Get pushable boxes in direction and pushability
-    let (box_coords, pushable) = pushable_boxes_in_direction(board, next_lhs_coord, direction);
-
-    // dbg!(box_coords.clone(), pushable);
+fn parse_moves(s: &str) -> Result<Vec<Direction>, WarehouseError> {
+    s.chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| Direction::new(c).ok_or(WarehouseError::UnknownMove(c)))
+        .collect()
+}
 
-    // If they are pushable
-    if pushable {
-        let current_coords_and_tiles = box_coords.iter().map(|coord| (coord, board.get(coord))).collect_vec();
-        for coord in box_coords.clone() {
-            // Set the coordinates to air
-            board.set(&coord, '.');
-        }
+fn parse_input(input: &str, factor: usize) -> Result<(Warehouse, Vec<Direction>), WarehouseError> {
+    let (map, moves) = input
+        .split_once("\n\n")
+        .ok_or(WarehouseError::MissingMoves)?;
+    let warehouse = Warehouse::parse(&widen(map, factor)?)?;
+    Ok((warehouse, parse_moves(moves)?))
+}
 
-        // Find the new coordinates by pushing them all in the direction
-        let pushed_coords_and_tiles = current_coords_and_tiles.into_iter().map(|((coord), tile)| {
-            let pushed_coord = coordinate_in_direction(*coord, direction).expect("should be able to push box");
-            (pushed_coord, tile)
-        });
-        // Write all the pushed coordinates back to the board
-        for (pushed_coord, pushed_tile) in pushed_coords_and_tiles {
-            board.set(&pushed_coord, pushed_tile)
-        }
-    }
+fn simulate(input: &str, factor: usize) -> Result<usize, WarehouseError> {
+    let (mut warehouse, moves) = parse_input(input, factor)?;
+    warehouse.run(&moves)?;
+    Ok(warehouse.gps_coordinate_sum())
+}
 
-    return pushable
+pub fn part_one(input: &str) -> Option<u32> {
+    simulate(input, 1).ok()?.try_into().ok()
 }
 
 pub fn part_two(input: &str) -> Option<u32> {
-    let mut board = Board::new(&preprocess(input.split("\n\n").nth(0)?))?;
-    let directions = input.split("\n\n").nth(1)?;
-
-    // Iteration:
-    //
-    let mut agent_pos = find_agent_or_fail(&board);
-
-    // println!("initial state:\n{}", board.as_string());
-    for direction_char in directions.lines().flat_map(|line| line.chars()) {
-        // println!("Direction char: {}, agent_pos: {:?}", direction_char, agent_pos);
-        // board.print();
-        let direction = Direction::new(direction_char).expect("Should parse direction");
-        agent_pos = step_part_2(&mut board, agent_pos, direction);
-        // println!("move {}:\n{}", direction_char, board.as_string());
-    }
-
-    // // Find sum of "GPS-coordinates"
-    let gps_coordinate_sum: usize =
-        board
-            .tiles
-            .iter()
-            .enumerate()
-            .flat_map(|(row, row_tiles)| {
-                row_tiles.into_iter().enumerate().map(move |(col, char)| {
-                    if *char == '[' {
-                        (100 * row) + col
-                    } else {
-                        0
-                    }
-                })
-            })
-            .sum();
-
-    Some(gps_coordinate_sum.try_into().expect("Should convert"))
-
-    // None
+    simulate(input, 2).ok()?.try_into().ok()
 }
 
 #[cfg(test)]
@@ -431,26 +383,25 @@ mod tests {
         assert_eq!(result, Some(9021));
     }
 
-
     #[test]
     fn test_part_two_4() {
         let result = part_two(&advent_of_code::template::read_file_part(
             "examples", DAY, 4,
         ));
-/*
-##############
-##...[].##..##
-##...@.[]...##
-##....[]....##
-##..........##
-##..........##
-##############
-
-100 * 1 + 5 = 105
-100 * 2 + 7 = 207
-100 * 3 + 6 = 306
-sum : 618
- */
+        /*
+        ##############
+        ##...[].##..##
+        ##...@.[]...##
+        ##....[]....##
+        ##..........##
+        ##..........##
+        ##############
+
+        100 * 1 + 5 = 105
+        100 * 2 + 7 = 207
+        100 * 3 + 6 = 306
+        sum : 618
+         */
         assert_eq!(result, Some(618));
     }
 
@@ -458,12 +409,143 @@ sum : 618
     fn test_preprocess() {
         let expected = advent_of_code::template::read_file_part("examples", DAY, 3);
 
-        let actual = preprocess(
+        let actual = widen(
             advent_of_code::template::read_file_part("examples", DAY, 1)
                 .split("\n\n")
-                .nth(0)
+                .next()
                 .unwrap(),
-        );
+            2,
+        )
+        .unwrap();
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn test_every_step_second_example() {
+        let input = advent_of_code::template::read_file_part("examples", DAY, 2);
+        let (mut warehouse, moves) = parse_input(&input, 1).unwrap();
+
+        // The state after each move, as listed in the puzzle text
+        let states = [
+            "#..O.O.#\n##@.O..#\n#...O..#\n#.#.O..#\n#...O..#\n#......#",
+            "#..O.O.#\n##@.O..#\n#...O..#\n#.#.O..#\n#...O..#\n#......#",
+            "#.@O.O.#\n##..O..#\n#...O..#\n#.#.O..#\n#...O..#\n#......#",
+            "#.@O.O.#\n##..O..#\n#...O..#\n#.#.O..#\n#...O..#\n#......#",
+            "#..@OO.#\n##..O..#\n#...O..#\n#.#.O..#\n#...O..#\n#......#",
+            "#...@OO#\n##..O..#\n#...O..#\n#.#.O..#\n#...O..#\n#......#",
+            "#...@OO#\n##..O..#\n#...O..#\n#.#.O..#\n#...O..#\n#......#",
+            "#....OO#\n##..@..#\n#...O..#\n#.#.O..#\n#...O..#\n#...O..#",
+            "#....OO#\n##..@..#\n#...O..#\n#.#.O..#\n#...O..#\n#...O..#",
+            "#....OO#\n##.@...#\n#...O..#\n#.#.O..#\n#...O..#\n#...O..#",
+            "#....OO#\n##.....#\n#..@O..#\n#.#.O..#\n#...O..#\n#...O..#",
+            "#....OO#\n##.....#\n#...@O.#\n#.#.O..#\n#...O..#\n#...O..#",
+            "#....OO#\n##.....#\n#....@O#\n#.#.O..#\n#...O..#\n#...O..#",
+            "#....OO#\n##.....#\n#.....O#\n#.#.O@.#\n#...O..#\n#...O..#",
+            "#....OO#\n##.....#\n#.....O#\n#.#O@..#\n#...O..#\n#...O..#",
+            "#....OO#\n##.....#\n#.....O#\n#.#O@..#\n#...O..#\n#...O..#",
+        ];
+        let walled = |state: &str| format!("########\n{}\n########\n", state);
+
+        let mut rendered = vec![warehouse.to_string()];
+        for direction in moves {
+            warehouse.step(direction).unwrap();
+            rendered.push(warehouse.to_string());
+        }
+        assert_eq!(rendered, states.map(walled).to_vec());
+        assert_eq!(warehouse.gps_coordinate_sum(), 2028);
+
+        // Undoing every move goes back through the same states
+        for state in states.iter().rev().skip(1) {
+            assert_eq!(warehouse.undo(), Ok(true));
+            assert_eq!(warehouse.to_string(), walled(state));
+        }
+        assert_eq!(warehouse.undo(), Ok(false));
+    }
+
+    #[test]
+    fn test_wide_boxes() {
+        let input = advent_of_code::template::read_file_part("examples", DAY, 4);
+        let (mut warehouse, moves) = parse_input(&input, 2).unwrap();
+        warehouse.run(&moves).unwrap();
+
+        let expected = "##############\n##...[].##..##\n##...@.[]...##\n##....[]....##\n##..........##\n##..........##\n##############\n";
+        assert_eq!(warehouse.to_string(), expected);
+        assert_eq!(warehouse.history.len(), moves.len());
+    }
+
+    #[test]
+    fn test_three_wide_boxes() {
+        let mut warehouse =
+            Warehouse::parse("#########\n#.......#\n#.[-]...#\n#..[-]..#\n#...@...#\n#########")
+                .unwrap();
+
+        // Pushes both boxes, since the lower one is under the upper one
+        assert_eq!(warehouse.step(Direction::North), Ok(true));
+        assert_eq!(
+            warehouse.to_string(),
+            "#########\n#.[-]...#\n#..[-]..#\n#...@...#\n#.......#\n#########\n"
+        );
+
+        // The upper box is against the wall
+        assert_eq!(warehouse.step(Direction::North), Ok(false));
+
+        assert_eq!(warehouse.step(Direction::East), Ok(true));
+        assert_eq!(warehouse.step(Direction::East), Ok(true));
+        assert_eq!(warehouse.step(Direction::North), Ok(true));
+        assert_eq!(warehouse.step(Direction::West), Ok(true));
+        assert_eq!(
+            warehouse.to_string(),
+            "#########\n#.[-]...#\n#.[-]@..#\n#.......#\n#.......#\n#########\n"
+        );
+        assert_eq!(warehouse.gps_coordinate_sum(), 102 + 202);
+
+        assert_eq!(warehouse.undo(), Ok(true));
+        assert_eq!(
+            warehouse.to_string(),
+            "#########\n#.[-]...#\n#..[-]@.#\n#.......#\n#.......#\n#########\n"
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            Warehouse::parse("#####\n#.x@#\n#####").err(),
+            Some(WarehouseError::UnexpectedTile {
+                tile: 'x',
+                coord: (1, 2)
+            })
+        );
+        assert_eq!(
+            Warehouse::parse("#####\n#[.@#\n#####").err(),
+            Some(WarehouseError::UnclosedBox((1, 1)))
+        );
+        assert_eq!(
+            Warehouse::parse("####\n#..#\n####").err(),
+            Some(WarehouseError::MissingRobot)
+        );
+        assert_eq!(
+            Warehouse::parse("####\n#@@#\n####").err(),
+            Some(WarehouseError::MultipleRobots)
+        );
+        assert_eq!(
+            simulate("####\n#@.#\n####\n\n>x", 1),
+            Err(WarehouseError::UnknownMove('x'))
+        );
+        assert_eq!(
+            simulate("####\n#@.#\n####", 1),
+            Err(WarehouseError::MissingMoves)
+        );
+
+        // Without walls around it, the robot can reach the edge
+        let mut warehouse = Warehouse::parse("@O.").unwrap();
+        assert_eq!(warehouse.step(Direction::East), Ok(true));
+        assert_eq!(
+            warehouse.step(Direction::East),
+            Err(WarehouseError::OutOfBounds((0, 2)))
+        );
+        assert_eq!(
+            warehouse.step(Direction::North),
+            Err(WarehouseError::OutOfBounds((0, 1)))
+        );
+    }
 }
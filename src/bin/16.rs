@@ -1,12 +1,11 @@
 use std::{
     cmp::Ordering,
     collections::{BinaryHeap, HashMap, HashSet},
-    usize,
 };
 
 use itertools::Itertools;
 
-advent_of_code::solution!(16);
+advent_of_code::solution!(16, debug = debug);
 
 // Implementation partly based on rust binary heap docs: https://doc.rust-lang.org/nightly/std/collections/binary_heap/index.html
 
@@ -75,7 +74,6 @@ impl PartialOrd for State {
 
 // Possible tiles
 const WALL: char = '#';
-const AIR: char = '.';
 const START: char = 'S';
 const GOAL: char = 'E';
 
@@ -105,20 +103,39 @@ impl Board {
     }
 }
 
+// The score of every move the reindeer can make
+#[derive(Copy, Clone, Debug)]
+struct Costs {
+    step: usize,
+    turn: usize,
+}
+
+const REINDEER_COSTS: Costs = Costs {
+    step: 1,
+    turn: 1000,
+};
+
 // (pose, additional_cost)
-fn adjacent_poses_and_additional_costs(board: &Board, pose: &Pose) -> Vec<(Pose, usize)> {
+fn adjacent_poses_and_additional_costs(
+    board: &Board,
+    pose: &Pose,
+    costs: Costs,
+) -> Vec<(Pose, usize)> {
     let mut adj: Vec<(Pose, usize)> = vec![];
 
     // Straight ahead
     if let Some(front_coord) = coord_in_direction(pose.coord, pose.direction) {
         // Check if there is a wall there
-        if board.get(&front_coord) != WALL {
+        if front_coord.0 < board.rows
+            && front_coord.1 < board.cols
+            && board.get(&front_coord) != WALL
+        {
             adj.push((
                 Pose {
                     coord: front_coord,
                     direction: pose.direction,
                 },
-                1,
+                costs.step,
             ));
         }
     }
@@ -130,151 +147,43 @@ fn adjacent_poses_and_additional_costs(board: &Board, pose: &Pose) -> Vec<(Pose,
                 coord: pose.coord,
                 direction: turned_dir,
             },
-            1000,
+            costs.turn,
         ));
     }
 
     adj
 }
 
-static ALL_DIRECTIONS: &'static [Direction] = &[
-    Direction::North,
-    Direction::East,
-    Direction::South,
-    Direction::West,
-];
-
-fn find_needle_or_fail(board: &Board, needle: char) -> Coord {
+fn find_needle(board: &Board, needle: char) -> Option<Coord> {
     for (i, row) in board.tiles.iter().enumerate() {
         for (j, tile) in row.iter().enumerate() {
             if *tile == needle {
-                return (i, j);
+                return Some((i, j));
             }
         }
     }
 
-    panic!("Couldn't find tile!");
+    None
 }
 
-fn djikstra_shortest_path_part_1(board: Board) -> Option<usize> {
-    // dist[node] = current shortest distance from `start` to `node`
-    let mut pose_cost: HashMap<Pose, usize> = board
-        .tiles
-        .iter()
-        .enumerate()
-        .flat_map(|(row_idx, row_tiles)| {
-            row_tiles.iter().enumerate().flat_map(move |(col_idx, _)| {
-                let coord = (row_idx, col_idx);
-                let cost = usize::MAX;
-                ALL_DIRECTIONS.iter().map(move |direction| {
-                    (
-                        Pose {
-                            coord,
-                            direction: *direction,
-                        },
-                        cost,
-                    )
-                })
-            })
-        })
-        .collect();
-
-    let mut heap = BinaryHeap::new();
+struct BestPaths {
+    cost: usize,
+    // Every tile that is part of at least one of the best paths
+    seats: HashSet<Coord>,
+}
 
-    let start_coord = find_needle_or_fail(&board, START);
+// Dijkstra that keeps every predecessor with an equally good cost, so the predecessors form a DAG
+// of all the best paths to every pose
+fn best_paths(board: &Board, costs: Costs) -> Option<BestPaths> {
     let start_pose = Pose {
-        coord: start_coord,
+        coord: find_needle(board, START)?,
         direction: Direction::East, // Always the case
     };
-    let goal_coord = find_needle_or_fail(&board, GOAL);
-    let goals: Vec<Pose> = ALL_DIRECTIONS
-        .iter()
-        .map(|dir| Pose {
-            coord: goal_coord,
-            direction: *dir,
-        })
-        .collect_vec();
-
-    // We're at `start`, with a zero cost
-    pose_cost.insert(start_pose, 0);
-    heap.push(State {
-        pose: start_pose,
-        cost: 0,
-    });
+    let goal_coord = find_needle(board, GOAL)?;
 
-    // Examine the frontier with lower cost nodes first (min-heap)
-    while let Some(State { pose, cost }) = heap.pop() {
-        // dbg!(pose.clone(), cost.clone());
-
-        // Alternatively we could have continued to find all shortest paths
-        if goals.contains(&pose) {
-            return Some(cost);
-        }
-
-        // Important as we may have already found a better way
-        if cost > pose_cost[&pose] {
-            continue;
-        }
-
-        // For each node we can reach, see if we can find a way with
-        // a lower cost going through this node
-        for (adj_pose, additional_cost) in adjacent_poses_and_additional_costs(&board, &pose) {
-            let next = State {
-                pose: adj_pose,
-                cost: cost + additional_cost,
-            };
-
-            // If so, add it to the frontier and continue
-            if next.cost < pose_cost[&next.pose] {
-                heap.push(next);
-                // Relaxation, we have now found a better way
-                pose_cost.insert(next.pose, next.cost);
-            }
-        }
-    }
-
-    // Goal not reachable
-    None
-}
-
-fn backtrack_all(
-    costs_and_predecessors: &HashMap<Pose, (usize, Vec<Pose>)>,
-    pose: Pose,
-) -> Vec<Pose> {
-    if let Some((_, predecessors)) = costs_and_predecessors.get(&pose) {
-        let mut out: Vec<Pose> = predecessors
-            .iter()
-            .flat_map(|predecessor| backtrack_all(costs_and_predecessors, *predecessor))
-            .collect();
-
-        out.push(pose);
-
-        return out;
-    } else {
-        return vec![];
-    }
-}
-
-fn djikstra_best_seats_part_2(board: Board) -> Option<HashSet<Coord>> {
-    // dist[node] = current shortest distance from `start` to `node`
     let mut pose_cost_and_predecessors: HashMap<Pose, (usize, Vec<Pose>)> = HashMap::new();
-
     let mut heap = BinaryHeap::new();
 
-    let start_coord = find_needle_or_fail(&board, START);
-    let start_pose = Pose {
-        coord: start_coord,
-        direction: Direction::East, // Always the case
-    };
-    let goal_coord = find_needle_or_fail(&board, GOAL);
-    let goals: Vec<Pose> = ALL_DIRECTIONS
-        .iter()
-        .map(|dir| Pose {
-            coord: goal_coord,
-            direction: *dir,
-        })
-        .collect_vec();
-
     // We're at `start`, with a zero cost
     pose_cost_and_predecessors.insert(start_pose, (0, vec![]));
     heap.push(State {
@@ -282,22 +191,14 @@ fn djikstra_best_seats_part_2(board: Board) -> Option<HashSet<Coord>> {
         cost: 0,
     });
 
-    let mut found_goal = false;
+    let mut best_cost: Option<usize> = None;
+    let mut goal_poses: Vec<Pose> = vec![];
 
     // Examine the frontier with lower cost nodes first (min-heap)
     while let Some(State { pose, cost }) = heap.pop() {
-        // dbg!(pose.clone(), cost.clone());
-
-        // Alternatively we could have continued to find all shortest paths
-        if goals.contains(&pose) {
-            // let all_coords = backtrack_all(pose_cost_and_predecessors, goal_coord);
-            // let unique_coords: HashSet<Coord> = all_coords.into_iter().collect();
-            // return Some(unique_coords);
-            println!("found goal");
-
-            found_goal = true;
-
-            continue;
+        // Everything left is more expensive than the best paths
+        if best_cost.is_some_and(|best_cost| cost > best_cost) {
+            break;
         }
 
         // Important as we may have already found a better way
@@ -306,107 +207,91 @@ fn djikstra_best_seats_part_2(board: Board) -> Option<HashSet<Coord>> {
             continue;
         }
 
+        if pose.coord == goal_coord {
+            best_cost = Some(cost);
+            goal_poses.push(pose);
+            continue;
+        }
+
         // For each node we can reach, see if we can find a way with
-        // a lower cost going through this node
-        for (adj_pose, additional_cost) in adjacent_poses_and_additional_costs(&board, &pose) {
+        // a lower or equal cost going through this node
+        for (adj_pose, additional_cost) in adjacent_poses_and_additional_costs(board, &pose, costs)
+        {
             let next = State {
                 pose: adj_pose,
                 cost: cost + additional_cost,
             };
 
-            // When exploring a new tile - insert into the hashmap
-            // When exploring a previous tile:
-            //   if the cost is better than the previous best, make a new vec with the pose and the cost
-            //   if the cost is the same -> append the pose and cost
-
-            // let (adj_pose_curr_best_cost, adj_pose_predecessors) =
-            if let Some((adj_pose_curr_best_cost, adj_pose_predecessors)) =
-                pose_cost_and_predecessors.get(&adj_pose)
-            {
-                if next.cost == *adj_pose_curr_best_cost {
-                    // Append and insert
-                    let mut new_adj_pose_predecessors = adj_pose_predecessors.clone();
-                    new_adj_pose_predecessors.push(pose);
-                    pose_cost_and_predecessors.insert(
-                        adj_pose,
-                        (next.cost, new_adj_pose_predecessors),
-                    );
-
-                    // No need to explore again by pushing to heap
-                } else if next.cost < *adj_pose_curr_best_cost {
+            match pose_cost_and_predecessors.get_mut(&adj_pose) {
+                // Another best way to the pose, which has already been pushed to the heap
+                Some((adj_cost, predecessors)) if next.cost == *adj_cost => predecessors.push(pose),
+                Some((adj_cost, _)) if next.cost > *adj_cost => {}
+                // Relaxation, we have now found a better way
+                _ => {
                     heap.push(next);
-                    // Relaxation, we have now found a better way
-                    pose_cost_and_predecessors.insert(next.pose, (next.cost, vec![pose]));
+                    pose_cost_and_predecessors.insert(adj_pose, (next.cost, vec![pose]));
                 }
-            } else {
-                heap.push(next);
-                // Relaxation, we have now found a better way
-                pose_cost_and_predecessors.insert(next.pose, (next.cost, vec![pose]));
             }
         }
     }
 
-    if found_goal {
-        let mut best_cost = usize::MAX;
-        for direction in ALL_DIRECTIONS {
-            if let Some(tup) = pose_cost_and_predecessors.get(&Pose{ coord: goal_coord, direction: *direction}) {
-                let (cost, _) = tup;
-                if *cost < best_cost {
-                    best_cost = *cost;
-                }
+    // Walk the predecessor DAG back from the goal, visiting each pose once
+    let mut visited: HashSet<Pose> = goal_poses.iter().cloned().collect();
+    let mut stack = goal_poses;
+    while let Some(pose) = stack.pop() {
+        for predecessor in &pose_cost_and_predecessors[&pose].1 {
+            if visited.insert(*predecessor) {
+                stack.push(*predecessor);
             }
         }
+    }
 
-        let mut goal_poses: Vec<Pose> = vec![];
-        for direction in ALL_DIRECTIONS {
-            let possible_pose = Pose{ coord: goal_coord, direction: *direction};
-            if let Some(tup) = pose_cost_and_predecessors.get(&possible_pose) {
-                let (cost, _) = tup;
-                if *cost == best_cost {
-                    goal_poses.push(possible_pose);
-                }
-            }
-        }
+    Some(BestPaths {
+        cost: best_cost?,
+        seats: visited.into_iter().map(|pose| pose.coord).collect(),
+    })
+}
 
-        let all_poses = goal_poses.iter().flat_map(|p| {
-            backtrack_all(
-                &pose_cost_and_predecessors,
-                *p
-            )
-        });
-        let unique_coords: HashSet<Coord> = all_poses
-            .into_iter()
-            .map(|some_pose| some_pose.coord)
-            .collect();
-        return Some(unique_coords);
-    }
+const SEAT: char = 'O';
 
-    // Goal not reachable
-    None
+// The maze with the seats marked, like in the puzzle text
+fn render(board: &Board, seats: &HashSet<Coord>) -> String {
+    board
+        .tiles
+        .iter()
+        .enumerate()
+        .map(|(row, tiles)| {
+            tiles
+                .iter()
+                .enumerate()
+                .map(|(col, tile)| {
+                    if seats.contains(&(row, col)) {
+                        SEAT
+                    } else {
+                        *tile
+                    }
+                })
+                .collect::<String>()
+        })
+        .join("\n")
 }
 
-pub fn part_one(input: &str) -> Option<u32> {
-    let board = Board::new(input).expect("Board should parse!");
+fn debug(input: &str) -> Option<String> {
+    let board = Board::new(input)?;
+    let best = best_paths(&board, REINDEER_COSTS)?;
+    Some(render(&board, &best.seats))
+}
 
-    let shortest_path_cost =
-        djikstra_shortest_path_part_1(board).expect("Should find shortest path!");
-    Some(shortest_path_cost.try_into().ok()?)
+pub fn part_one(input: &str) -> Option<u32> {
+    let board = Board::new(input)?;
+    let best = best_paths(&board, REINDEER_COSTS)?;
+    best.cost.try_into().ok()
 }
 
 pub fn part_two(input: &str) -> Option<u32> {
-    // Idea: Modify implementation from part 1 by
-    // Make a HashMap predecessors from pose to Vec<(prev_pose, cost)>. Use this to mark that a tile is visited with the best cost so far. When doing this we can replace the pose_cost HashMap
-    // When exploring a new tile - insert into the hashmap
-    // When exploring a previous tile:
-    //   if the cost is better than the previous best, make a new vec with the pose and the cost
-    //   if the cost is the same -> append the pose and cost
-    let board = Board::new(input).expect("Board should parse!");
-
-    let unique_coords = djikstra_best_seats_part_2(board).expect("Should find shortest path!");
-    println!("unique coords: {:?}", unique_coords);
-    Some(unique_coords.len().try_into().ok()?)
-
-    // None
+    let board = Board::new(input)?;
+    let best = best_paths(&board, REINDEER_COSTS)?;
+    best.seats.len().try_into().ok()
 }
 
 #[cfg(test)]
@@ -444,4 +329,44 @@ mod tests {
         ));
         assert_eq!(result, Some(64));
     }
+
+    const SMALL_MAZE: &str = "#####\n#..E#\n#S..#\n#####";
+
+    #[test]
+    fn test_render_seats() {
+        let board = Board::new(SMALL_MAZE).unwrap();
+        let best = best_paths(&board, REINDEER_COSTS).unwrap();
+        assert_eq!(best.cost, 1003);
+        assert_eq!(render(&board, &best.seats), "#####\n#..O#\n#OOO#\n#####");
+    }
+
+    #[test]
+    fn test_free_turns() {
+        // With free turns, every shortest path is a best path
+        let board = Board::new(SMALL_MAZE).unwrap();
+        let best = best_paths(&board, Costs { step: 1, turn: 0 }).unwrap();
+        assert_eq!(best.cost, 3);
+        assert_eq!(render(&board, &best.seats), "#####\n#OOO#\n#OOO#\n#####");
+    }
+
+    #[test]
+    fn test_render_example() {
+        let input = advent_of_code::template::read_file_part("examples", DAY, 1);
+        let board = Board::new(&input).unwrap();
+        let best = best_paths(&board, REINDEER_COSTS).unwrap();
+        let rendered = render(&board, &best.seats);
+
+        assert_eq!(rendered.matches(SEAT).count(), 45);
+        for (original, marked) in input.lines().zip(rendered.lines()) {
+            for (tile, marked_tile) in original.chars().zip(marked.chars()) {
+                assert!(marked_tile == tile || (marked_tile == SEAT && tile != WALL));
+            }
+        }
+    }
+
+    #[test]
+    fn test_unreachable_goal() {
+        let result = part_one("#####\n#S#E#\n#####");
+        assert_eq!(result, None);
+    }
 }
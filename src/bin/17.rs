@@ -1,13 +1,70 @@
+use std::{collections::HashSet, fmt};
+
 use itertools::Itertools;
 
-advent_of_code::solution!(17);
+advent_of_code::solution!(17, debug = debug);
 
+#[derive(Clone, Copy)]
 struct Instruction {
     opcode: i64,
     operand: i64,
 }
 
-#[derive(Clone, Copy)]
+fn combo_operand_name(operand: i64) -> String {
+    match operand {
+        4 => "a".to_string(),
+        5 => "b".to_string(),
+        6 => "c".to_string(),
+        _ => operand.to_string(),
+    }
+}
+
+impl Instruction {
+    fn mnemonic(&self) -> &'static str {
+        match self.opcode {
+            0 => "adv",
+            1 => "bxl",
+            2 => "bst",
+            3 => "jnz",
+            4 => "bxc",
+            5 => "out",
+            6 => "bdv",
+            7 => "cdv",
+            _ => "???",
+        }
+    }
+
+    // What the instruction does, written as pseudocode
+    fn effect(&self) -> String {
+        let literal = self.operand;
+        let combo = combo_operand_name(self.operand);
+        match self.opcode {
+            0 => format!("a = a >> {}", combo),
+            1 => format!("b = b ^ {}", literal),
+            2 => format!("b = {} % 8", combo),
+            3 => format!("if a != 0 goto {}", literal),
+            4 => "b = b ^ c".to_string(),
+            5 => format!("output {} % 8", combo),
+            6 => format!("b = a >> {}", combo),
+            7 => format!("c = a >> {}", combo),
+            _ => "invalid opcode".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {:<2} ; {}",
+            self.mnemonic(),
+            self.operand,
+            self.effect()
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 struct Computer {
     a: i64,
     b: i64,
@@ -17,7 +74,7 @@ struct Computer {
 impl Computer {
     fn combo_operand(&self, operand: i64) -> i64 {
         match operand {
-            0 | 1 | 2 | 3 => operand,
+            0..=3 => operand,
             4 => self.a,
             5 => self.b,
             6 => self.c,
@@ -46,7 +103,7 @@ fn adv(computer: &Computer, instruction: Instruction) -> StepOutput {
             .expect("Should convert combo operand"),
     );
     let result = numerator / denominator;
-    let mut next_state = computer.clone();
+    let mut next_state = *computer;
     next_state.a = result;
     StepOutput {
         next_state,
@@ -59,7 +116,7 @@ fn adv(computer: &Computer, instruction: Instruction) -> StepOutput {
 // then stores the result in register B.
 fn bxl(computer: &Computer, instruction: Instruction) -> StepOutput {
     let result = computer.b ^ instruction.operand;
-    let mut next_state = computer.clone();
+    let mut next_state = *computer;
     next_state.b = result;
     StepOutput {
         next_state,
@@ -73,7 +130,7 @@ fn bxl(computer: &Computer, instruction: Instruction) -> StepOutput {
 fn bst(computer: &Computer, instruction: Instruction) -> StepOutput {
     let combo_operand = computer.combo_operand(instruction.operand);
     let result = combo_operand % 8;
-    let mut next_state = computer.clone();
+    let mut next_state = *computer;
     next_state.b = result;
     StepOutput {
         next_state,
@@ -86,7 +143,7 @@ fn bst(computer: &Computer, instruction: Instruction) -> StepOutput {
 // However, if the A register is not zero, it jumps by setting the instruction pointer to the value of its literal operand;
 // if this instruction jumps, the instruction pointer is not increased by 2 after this instruction.
 fn jnz(computer: &Computer, instruction: Instruction) -> StepOutput {
-    let next_state = computer.clone();
+    let next_state = *computer;
     if computer.a == 0 {
         StepOutput {
             next_state,
@@ -109,9 +166,9 @@ fn jnz(computer: &Computer, instruction: Instruction) -> StepOutput {
 
 // The bxc instruction (opcode 4) calculates the bitwise XOR of register B and register C,
 // then stores the result in register B. (For legacy reasons, this instruction reads an operand but ignores it.)
-fn bxc(computer: &Computer, instruction: Instruction) -> StepOutput {
+fn bxc(computer: &Computer, _instruction: Instruction) -> StepOutput {
     let result = computer.b ^ computer.c;
-    let mut next_state = computer.clone();
+    let mut next_state = *computer;
     next_state.b = result;
     StepOutput {
         next_state,
@@ -125,7 +182,7 @@ fn bxc(computer: &Computer, instruction: Instruction) -> StepOutput {
 fn out(computer: &Computer, instruction: Instruction) -> StepOutput {
     let combo_operand = computer.combo_operand(instruction.operand);
     let result = combo_operand % 8;
-    let mut next_state = computer.clone();
+    let next_state = *computer;
     StepOutput {
         next_state,
         output: Some(result),
@@ -144,7 +201,7 @@ fn bdv(computer: &Computer, instruction: Instruction) -> StepOutput {
             .expect("Should convert combo operand"),
     );
     let result = numerator / denominator;
-    let mut next_state = computer.clone();
+    let mut next_state = *computer;
     next_state.b = result;
     StepOutput {
         next_state,
@@ -164,7 +221,7 @@ fn cdv(computer: &Computer, instruction: Instruction) -> StepOutput {
             .expect("Should convert combo operand"),
     );
     let result = numerator / denominator;
-    let mut next_state = computer.clone();
+    let mut next_state = *computer;
     next_state.c = result;
     StepOutput {
         next_state,
@@ -188,7 +245,7 @@ fn execute_instruction(computer: &Computer, instruction: Instruction) -> StepOut
 }
 
 fn parse(input: &str) -> Option<(Computer, Vec<i64>)> {
-    let a: i64 = input.lines().nth(0)?.split(" ").last()?.parse().ok()?;
+    let a: i64 = input.lines().next()?.split(" ").last()?.parse().ok()?;
     let b: i64 = input.lines().nth(1)?.split(" ").last()?.parse().ok()?;
     let c: i64 = input.lines().nth(2)?.split(" ").last()?.parse().ok()?;
 
@@ -206,71 +263,297 @@ fn parse(input: &str) -> Option<(Computer, Vec<i64>)> {
     Some((computer, instructions))
 }
 
+#[derive(Debug, PartialEq)]
+enum Stop {
+    Halted,
+    Breakpoint(usize),
+    BudgetExhausted,
+}
+
+// The computer together with its program, decoded once up front. Since jumps may land on any
+// address, the instruction starting at every address is decoded.
+struct Vm {
+    computer: Computer,
+    program: Vec<Instruction>,
+    instruction_pointer: usize,
+    executed: usize,
+    budget: Option<usize>,
+    breakpoints: HashSet<usize>,
+}
+
+impl Vm {
+    fn new(computer: Computer, instructions: &[i64]) -> Vm {
+        let program = instructions
+            .iter()
+            .tuple_windows()
+            .map(|(opcode, operand)| Instruction {
+                opcode: *opcode,
+                operand: *operand,
+            })
+            .collect();
+
+        Vm {
+            computer,
+            program,
+            instruction_pointer: 0,
+            executed: 0,
+            budget: None,
+            breakpoints: HashSet::new(),
+        }
+    }
+
+    // Limits the total number of instructions the VM will execute
+    fn with_budget(mut self, budget: usize) -> Vm {
+        self.budget = Some(budget);
+        self
+    }
+
+    fn add_breakpoint(&mut self, instruction_pointer: usize) {
+        self.breakpoints.insert(instruction_pointer);
+    }
+
+    fn current_instruction(&self) -> Option<Instruction> {
+        self.program.get(self.instruction_pointer).copied()
+    }
+
+    // Executes a single instruction, returning its output if any
+    fn step(&mut self) -> Result<Option<i64>, Stop> {
+        let instruction = self.current_instruction().ok_or(Stop::Halted)?;
+        if self.budget.is_some_and(|budget| self.executed >= budget) {
+            return Err(Stop::BudgetExhausted);
+        }
+
+        let step_output = execute_instruction(&self.computer, instruction);
+
+        self.executed += 1;
+        self.instruction_pointer = step_output
+            .instruction_pointer
+            .unwrap_or(self.instruction_pointer + 2);
+        self.computer = step_output.next_state;
+
+        Ok(step_output.output)
+    }
+
+    // Runs until the program halts, the budget runs out or a breakpoint is reached. The
+    // instruction at the current position is always executed, so that a stopped VM can resume.
+    fn run_until_stop(&mut self, outputs: &mut Vec<i64>) -> Stop {
+        let mut first = true;
+        loop {
+            if !first && self.breakpoints.contains(&self.instruction_pointer) {
+                return Stop::Breakpoint(self.instruction_pointer);
+            }
+            first = false;
+
+            match self.step() {
+                Ok(Some(output)) => outputs.push(output),
+                Ok(None) => {}
+                Err(stop) => return stop,
+            }
+        }
+    }
+
+    // Lazily runs the program, yielding outputs as they are produced. Breakpoints are ignored.
+    fn outputs(&mut self) -> Outputs<'_> {
+        Outputs { vm: self }
+    }
+}
+
+struct Outputs<'a> {
+    vm: &'a mut Vm,
+}
+
+impl Iterator for Outputs<'_> {
+    type Item = i64;
+
+    fn next(&mut self) -> Option<i64> {
+        loop {
+            if let Some(output) = self.vm.step().ok()? {
+                return Some(output);
+            }
+        }
+    }
+}
+
 pub fn part_one(input: &str) -> Option<String> {
     let (initial_computer, instructions) = parse(input).expect("Should parse input!");
 
-    let (final_computer, outputs) = simulate(initial_computer, instructions);
-
-    let out_string: String = outputs
-        .iter()
-        .map(|output| output.to_string())
-        .join(",")
-        .to_string();
+    let (_, outputs) = simulate(initial_computer, &instructions);
+    let out_string = outputs.iter().map(|output| output.to_string()).join(",");
 
     Some(out_string)
 }
 
-fn simulate(initial_computer: Computer, instructions: Vec<i64>) -> (Computer, Vec<i64>) {
-    let mut computer = initial_computer.clone();
-    let mut instruction_pointer: usize = 0;
-    let mut outputs: Vec<i64> = vec![];
+fn simulate(initial_computer: Computer, instructions: &[i64]) -> (Computer, Vec<i64>) {
+    let mut vm = Vm::new(initial_computer, instructions);
+    let mut outputs = vec![];
+    vm.run_until_stop(&mut outputs);
+    (vm.computer, outputs)
+}
 
-    while instruction_pointer < instructions.len() {
-        let (opcode, operand) = instructions
-            .clone()
-            .into_iter()
-            .tuple_windows::<(_, _)>()
-            .nth(instruction_pointer)
-            .expect("Should find instruction");
-        let instruction = Instruction { opcode, operand };
+// Lists the program in mnemonic form, one instruction per line prefixed by its address
+fn disassemble(instructions: &[i64]) -> String {
+    instructions
+        .chunks(2)
+        .enumerate()
+        .filter_map(|(i, chunk)| match chunk {
+            [opcode, operand] => {
+                let instruction = Instruction {
+                    opcode: *opcode,
+                    operand: *operand,
+                };
+                Some(format!("{:>2}: {}", 2 * i, instruction))
+            }
+            _ => None,
+        })
+        .join("\n")
+}
 
-        let step_output = execute_instruction(&computer, instruction);
+struct TraceStep {
+    instruction_pointer: usize,
+    instruction: Instruction,
+    before: Computer,
+    output: Option<i64>,
+}
 
-        if let Some(output) = step_output.output {
-            outputs.push(output);
+impl fmt::Display for TraceStep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:>2}: {} {:<2} | a={} b={} c={}",
+            self.instruction_pointer,
+            self.instruction.mnemonic(),
+            self.instruction.operand,
+            self.before.a,
+            self.before.b,
+            self.before.c
+        )?;
+        if let Some(output) = self.output {
+            write!(f, " -> {}", output)?;
         }
-        instruction_pointer = step_output
-            .instruction_pointer
-            .unwrap_or(instruction_pointer + 2);
-        computer = step_output.next_state;
+        Ok(())
     }
-    (computer, outputs)
 }
 
-// 0 : adv - write to a
-// 1 : bxl - write to b
-// 2 : bst - write to b
-// 3 : jnz - jump
-// 4 : bxc - write to b
-// 5 : out - output
-// 6 : bdv - read a, write to b
-// 7 : cdv - read a, write to c
+// Runs the program like `simulate`, but records every executed instruction and the registers before it
+fn trace(initial_computer: Computer, instructions: &[i64]) -> Vec<TraceStep> {
+    let mut vm = Vm::new(initial_computer, instructions);
+    let mut steps: Vec<TraceStep> = vec![];
+
+    while let Some(instruction) = vm.current_instruction() {
+        let instruction_pointer = vm.instruction_pointer;
+        let before = vm.computer;
+        let Ok(output) = vm.step() else {
+            break;
+        };
+
+        steps.push(TraceStep {
+            instruction_pointer,
+            instruction,
+            before,
+            output,
+        });
+    }
+    steps
+}
 
-// 0 is only that writes to a
-// 7 is only that writes to c
+// Runs the program one pass through its loop at a time, by breaking on the target of its jump.
+// Returns the registers at the start of every pass and what the pass printed.
+fn loop_passes(initial_computer: Computer, instructions: &[i64]) -> Vec<(Computer, Vec<i64>)> {
+    let mut vm = Vm::new(initial_computer, instructions);
+    let jump_targets = instructions
+        .chunks(2)
+        .filter(|chunk| chunk[0] == 3)
+        .filter_map(|chunk| usize::try_from(*chunk.get(1)?).ok());
+    for target in jump_targets {
+        vm.add_breakpoint(target);
+    }
+
+    let mut passes = vec![];
+    loop {
+        let before = vm.computer;
+        let mut outputs = vec![];
+        let stop = vm.run_until_stop(&mut outputs);
+        passes.push((before, outputs));
+        if !matches!(stop, Stop::Breakpoint(_)) {
+            return passes;
+        }
+    }
+}
+
+// The program in mnemonic form, every instruction it executes and a summary of every pass
+// through its loop
+fn debug(input: &str) -> Option<String> {
+    let (initial_computer, instructions) = parse(input)?;
+    let steps = trace(initial_computer, &instructions).iter().join("\n");
+    let passes = loop_passes(initial_computer, &instructions)
+        .iter()
+        .enumerate()
+        .map(|(i, (before, outputs))| {
+            format!(
+                "pass {:>2} | a={} b={} c={} -> {}",
+                i + 1,
+                before.a,
+                before.b,
+                before.c,
+                outputs.iter().join(",")
+            )
+        })
+        .join("\n");
+    Some(format!(
+        "{}\n\n{}\n\n{}",
+        disassemble(&instructions),
+        steps,
+        passes
+    ))
+}
+
+// Quine-style programs consume A a few bits at a time by a single `adv` with a literal operand,
+// printing one value per iteration until A runs out. Returns how many bits are consumed per output.
+fn a_shift(instructions: &[i64]) -> Option<u32> {
+    let shifts = instructions
+        .chunks(2)
+        .filter(|chunk| chunk[0] == 0)
+        .map(|chunk| chunk.get(1).copied())
+        .collect::<Option<Vec<i64>>>()?;
+
+    match shifts[..] {
+        [shift @ 1..=3] => shift.try_into().ok(),
+        _ => None,
+    }
+}
 
-fn a_from_components(components: Vec<i64>) -> i64 {
+fn a_from_components(components: &[i64], shift: u32) -> i64 {
     components
         .iter()
         .enumerate()
-        .map(|(i, c)| c * 8i64.pow(i.try_into().expect("Should convert")))
+        .map(|(i, c)| c << (shift as usize * i))
         .sum()
 }
 
-fn last_n_elements(v: Vec<i64>, n: usize) -> Vec<i64> {
-    v.iter().rev().take(n).rev().cloned().collect_vec()
+fn last_n_elements(v: &[i64], n: usize) -> &[i64] {
+    &v[v.len() - n..]
 }
 
+// Candidates that don't terminate within this many instructions are rejected
+const CANDIDATE_BUDGET: usize = 100_000;
+
+// Runs the program and checks that it outputs exactly the expected values, stopping at the first mismatch
+fn outputs_exactly(computer: Computer, instructions: &[i64], expected: &[i64]) -> bool {
+    let mut vm = Vm::new(computer, instructions).with_budget(CANDIDATE_BUDGET);
+    let mut count = 0;
+    for output in vm.outputs() {
+        if expected.get(count) != Some(&output) {
+            return false;
+        }
+        count += 1;
+    }
+    count == expected.len() && vm.current_instruction().is_none()
+}
+
+// Since the last output only depends on the most significant bits of A, A can be built backwards
+// from the end of the program, a few bits at a time, only keeping the candidates that reproduce
+// the corresponding tail of the program.
+//
 // procedure backtrack(P, c) is
 //     if reject(P, c) then return
 //     if accept(P, c) then output(P, c)
@@ -279,65 +562,65 @@ fn last_n_elements(v: Vec<i64>, n: usize) -> Vec<i64> {
 //         backtrack(P, s)
 //         s ← next(P, s)
 fn find_a_components(
-    instructions: &Vec<i64>,
+    initial_computer: Computer,
+    instructions: &[i64],
+    shift: u32,
     a_components: Vec<i64>,
     solutions: &mut Vec<Vec<i64>>,
 ) {
-    if a_components.len() != 0 {
+    if !a_components.is_empty() {
+        if a_components.len() > instructions.len() {
+            return;
+        }
+
         // Find desired partial output
-        let partial_output_length = a_components.len();
-        let desired_partial_output = last_n_elements(instructions.clone(), partial_output_length);
+        let desired_partial_output = last_n_elements(instructions, a_components.len());
 
-        let (_, output) = simulate(
-            Computer {
-                a: a_from_components(a_components.clone()),
-                b: 0,
-                c: 0,
-            },
-            instructions.clone(),
-        );
+        let computer = Computer {
+            a: a_from_components(&a_components, shift),
+            ..initial_computer
+        };
 
-        if output != desired_partial_output {
+        if !outputs_exactly(computer, instructions, desired_partial_output) {
             return;
         }
 
-        if output == *instructions {
+        if desired_partial_output.len() == instructions.len() {
             solutions.push(a_components);
             return;
         }
     }
 
-    for i in 0..8 {
-        for j in 0..8 {
-            let next_a_components = vec![i, j]
-                .iter()
-                .clone()
-                .chain(a_components.iter())
-                .cloned()
-                .collect_vec();
-            find_a_components(instructions, next_a_components, solutions);
-        }
+    for i in 0..(1 << shift) {
+        let next_a_components = [i].iter().chain(a_components.iter()).cloned().collect_vec();
+        find_a_components(
+            initial_computer,
+            instructions,
+            shift,
+            next_a_components,
+            solutions,
+        );
     }
 }
 
-// Simulate either in the
-
 pub fn part_two(input: &str) -> Option<i64> {
-    let (_, instructions) = parse(input).expect("Should parse input!");
+    let (initial_computer, instructions) = parse(input).expect("Should parse input!");
+    let shift = a_shift(&instructions)?;
 
-    let a_components: Vec<i64> = vec![];
     let mut solutions: Vec<Vec<i64>> = vec![];
 
-    find_a_components(&instructions, a_components, &mut solutions);
-
-    println!("solutions: {:?}", solutions.clone());
+    find_a_components(
+        initial_computer,
+        &instructions,
+        shift,
+        vec![],
+        &mut solutions,
+    );
 
-    let smallest_a = solutions
+    solutions
         .iter()
-        .map(|sol| a_from_components(sol.clone()))
-        .min();
-
-    smallest_a
+        .map(|sol| a_from_components(sol, shift))
+        .min()
 }
 
 #[cfg(test)]
@@ -347,14 +630,14 @@ mod tests {
     // If register C contains 9, the program 2,6 would set register B to 1.
     #[test]
     fn test_first_example() {
-        let (computer, outputs) = simulate(Computer { a: 0, b: 0, c: 9 }, vec![2, 6]);
+        let (computer, _) = simulate(Computer { a: 0, b: 0, c: 9 }, &[2, 6]);
         assert_eq!(1, computer.b);
     }
 
     // If register A contains 10, the program 5,0,5,1,5,4 would output 0,1,2.
     #[test]
     fn test_second_example() {
-        let (computer, outputs) = simulate(Computer { a: 10, b: 0, c: 0 }, vec![5, 0, 5, 1, 5, 4]);
+        let (_, outputs) = simulate(Computer { a: 10, b: 0, c: 0 }, &[5, 0, 5, 1, 5, 4]);
         assert_eq!(vec![0, 1, 2], outputs);
     }
 
@@ -367,7 +650,7 @@ mod tests {
                 b: 0,
                 c: 0,
             },
-            vec![0, 1, 5, 4, 3, 0],
+            &[0, 1, 5, 4, 3, 0],
         );
         assert_eq!(vec![4, 2, 5, 6, 7, 7, 7, 7, 3, 1, 0], outputs);
         assert_eq!(0, computer.a);
@@ -376,33 +659,158 @@ mod tests {
     // If register B contains 29, the program 1,7 would set register B to 26.
     #[test]
     fn test_fourth_example() {
-        let (computer, outputs) = simulate(Computer { a: 0, b: 29, c: 0 }, vec![1, 7]);
+        let (computer, _) = simulate(Computer { a: 0, b: 29, c: 0 }, &[1, 7]);
         assert_eq!(26, computer.b);
     }
 
     // If register B contains 2024 and register C contains 43690, the program 4,0 would set register B to 44354.
     #[test]
     fn test_fifth_example() {
-        let (computer, outputs) = simulate(
+        let (computer, _) = simulate(
             Computer {
                 a: 0,
                 b: 2024,
                 c: 43690,
             },
-            vec![4, 0],
+            &[4, 0],
         );
         assert_eq!(44354, computer.b);
     }
 
+    #[test]
+    fn test_vm_breakpoint() {
+        let mut vm = Vm::new(
+            Computer {
+                a: 2024,
+                b: 0,
+                c: 0,
+            },
+            &[0, 1, 5, 4, 3, 0],
+        );
+        vm.add_breakpoint(2);
+
+        let mut outputs = vec![];
+        assert_eq!(vm.run_until_stop(&mut outputs), Stop::Breakpoint(2));
+        assert_eq!(vm.computer.a, 1012);
+        assert!(outputs.is_empty());
+
+        assert_eq!(vm.run_until_stop(&mut outputs), Stop::Breakpoint(2));
+        assert_eq!(vm.computer.a, 506);
+        assert_eq!(outputs, vec![4]);
+    }
+
+    #[test]
+    fn test_vm_budget() {
+        // Loops forever, since A is never changed
+        let mut vm = Vm::new(Computer { a: 1, b: 0, c: 0 }, &[5, 4, 3, 0]).with_budget(10);
+
+        let mut outputs = vec![];
+        assert_eq!(vm.run_until_stop(&mut outputs), Stop::BudgetExhausted);
+        assert_eq!(outputs, vec![1; 5]);
+        assert_eq!(vm.step(), Err(Stop::BudgetExhausted));
+    }
+
+    #[test]
+    fn test_vm_streaming_outputs() {
+        let mut vm = Vm::new(
+            Computer {
+                a: 2024,
+                b: 0,
+                c: 0,
+            },
+            &[0, 1, 5, 4, 3, 0],
+        );
+        let first_three = vm.outputs().take(3).collect_vec();
+        assert_eq!(first_three, vec![4, 2, 5]);
+        assert_eq!(vm.computer.a, 253);
+
+        let rest = vm.outputs().collect_vec();
+        assert_eq!(rest, vec![6, 7, 7, 7, 7, 3, 1, 0]);
+        assert_eq!(vm.step(), Err(Stop::Halted));
+    }
+
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some("4,6,3,5,6,3,5,2,1,0".to_string()));
     }
 
+    #[test]
+    fn test_disassemble() {
+        let result = disassemble(&[0, 3, 5, 4, 3, 0]);
+        assert_eq!(
+            result,
+            [
+                " 0: adv 3  ; a = a >> 3",
+                " 2: out 4  ; output a % 8",
+                " 4: jnz 0  ; if a != 0 goto 0",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_trace() {
+        let steps = trace(Computer { a: 10, b: 0, c: 0 }, &[5, 0, 5, 1, 5, 4]);
+        let lines = steps.iter().map(|step| step.to_string()).collect_vec();
+        assert_eq!(
+            lines,
+            vec![
+                " 0: out 0  | a=10 b=0 c=0 -> 0",
+                " 2: out 1  | a=10 b=0 c=0 -> 1",
+                " 4: out 4  | a=10 b=0 c=0 -> 2",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_trace_matches_simulate() {
+        let (computer, instructions) =
+            parse(&advent_of_code::template::read_file("examples", DAY)).unwrap();
+        let traced_outputs = trace(computer, &instructions)
+            .iter()
+            .filter_map(|step| step.output)
+            .collect_vec();
+        let (_, outputs) = simulate(computer, &instructions);
+        assert_eq!(traced_outputs, outputs);
+    }
+
+    #[test]
+    fn test_loop_passes() {
+        let passes = loop_passes(
+            Computer {
+                a: 2024,
+                b: 0,
+                c: 0,
+            },
+            &[0, 3, 5, 4, 3, 0],
+        );
+        let summary = passes
+            .iter()
+            .map(|(before, outputs)| (before.a, outputs.clone()))
+            .collect_vec();
+        assert_eq!(
+            summary,
+            vec![(2024, vec![5]), (253, vec![7]), (31, vec![3]), (3, vec![0])]
+        );
+
+        // Without a jump the whole program is a single pass
+        let passes = loop_passes(Computer { a: 10, b: 0, c: 0 }, &[5, 0, 5, 4]);
+        assert_eq!(passes.len(), 1);
+        assert_eq!(passes[0].1, vec![0, 2]);
+    }
+
     #[test]
     fn test_part_two() {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, None);
     }
+
+    #[test]
+    fn test_part_two_quine() {
+        let result = part_two(&advent_of_code::template::read_file_part(
+            "examples", DAY, 2,
+        ));
+        assert_eq!(result, Some(117440));
+    }
 }
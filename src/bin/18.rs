@@ -4,8 +4,19 @@ use itertools::Itertools;
 
 advent_of_code::solution!(18);
 
+// Size of the memory space and how many bytes have fallen in part one, for the real input
+const MEMORY_SIZE: usize = 71;
+const PART_ONE_BYTES: usize = 1024;
+
 type Coord = (usize, usize);
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum MemoryError {
+    // The memory space has no cells, so there is no start or exit
+    Empty,
+    OutOfRange(Coord),
+}
+
 struct Board {
     occupied: HashSet<Coord>,
     rows: usize,
@@ -13,8 +24,9 @@ struct Board {
 }
 
 fn parse_coord(s: &str) -> Option<Coord> {
-    let first: usize = s.split(",").nth(0)?.parse().ok()?;
-    let second: usize = s.split(",").nth(1)?.parse().ok()?;
+    let (first, second) = s.split_once(',')?;
+    let first: usize = first.parse().ok()?;
+    let second: usize = second.parse().ok()?;
 
     Some((first, second))
 }
@@ -41,20 +53,30 @@ fn neighbor_coordinates(coord: &Coord, rows: usize, cols: usize) -> Vec<Coord> {
     neighbors
 }
 
+fn parse_bytes(input: &str) -> Option<Vec<Coord>> {
+    input.lines().map(parse_coord).collect()
+}
+
+// Every byte has to fall inside the memory space. Checked once up front, so that a byte out of
+// range is never mistaken for one that blocks the exit.
+fn check_bytes(bytes: &[Coord], rows: usize, cols: usize) -> Result<(), MemoryError> {
+    if rows == 0 || cols == 0 {
+        return Err(MemoryError::Empty);
+    }
+    match bytes.iter().find(|byte| byte.0 >= cols || byte.1 >= rows) {
+        Some(byte) => Err(MemoryError::OutOfRange(*byte)),
+        None => Ok(()),
+    }
+}
+
 impl Board {
-    fn new(s: Vec<&str>, is_test: bool) -> Option<Board> {
-        let rows: usize = if is_test { 7 } else { 71 };
-        let cols: usize = rows;
-        let tiles: HashSet<Coord> = s
-            .iter()
-            .map(|line| parse_coord(&line))
-            .collect::<Option<HashSet<_>>>()?;
-
-        Some(Board {
-            occupied: tiles,
+    // Expects bytes that passed `check_bytes`
+    fn new(bytes: &[Coord], rows: usize, cols: usize) -> Board {
+        Board {
+            occupied: bytes.iter().copied().collect(),
             rows,
             cols,
-        })
+        }
     }
 }
 
@@ -64,9 +86,9 @@ fn backtrack_steps(predecessors: &HashMap<Coord, Coord>, start: Coord, current:
         .expect("Should have predecessor!");
 
     if predecessor == start {
-        return 1;
+        1
     } else {
-        return 1 + backtrack_steps(predecessors, start, predecessor);
+        1 + backtrack_steps(predecessors, start, predecessor)
     }
 }
 
@@ -76,7 +98,13 @@ fn bfs(board: &Board) -> Option<u32> {
     let mut predecessors: HashMap<Coord, Coord> = HashMap::new();
     let mut queue: VecDeque<Coord> = VecDeque::new();
     let start: Coord = (0, 0);
-    let goal: Coord = (board.rows - 1, board.cols - 1);
+    let goal: Coord = (board.cols - 1, board.rows - 1);
+    if board.occupied.contains(&start) {
+        return None;
+    }
+    if start == goal {
+        return Some(0);
+    }
     queue.push_back(start);
     visited.insert(start);
 
@@ -92,48 +120,70 @@ fn bfs(board: &Board) -> Option<u32> {
             .collect_vec();
 
         for neigh in neighbors {
-            visited.insert(neigh.clone());
-            predecessors.insert(neigh.clone(), coord);
-            queue.push_back(neigh.clone());
+            visited.insert(neigh);
+            predecessors.insert(neigh, coord);
+            queue.push_back(neigh);
         }
     }
 
     None // No solution
 }
 
-pub fn part_one(input: &str) -> Option<u32> {
-    let is_test = input.lines().count() == 25;
-    let bytes_to_take: usize = if is_test { 12 } else { 1024 };
+// Steps to the exit after the first `fallen` bytes, or `None` if it can't be reached
+fn fallen_path(bytes: &[Coord], rows: usize, cols: usize, fallen: usize) -> Option<u32> {
+    bfs(&Board::new(&bytes[..fallen.min(bytes.len())], rows, cols))
+}
 
-    let lines: Vec<&str> = input.lines().take(bytes_to_take).collect();
-    let board = Board::new(lines, is_test).expect("Should parse board!");
+fn shortest_path(
+    bytes: &[Coord],
+    rows: usize,
+    cols: usize,
+    fallen: usize,
+) -> Result<Option<u32>, MemoryError> {
+    check_bytes(bytes, rows, cols)?;
+    Ok(fallen_path(bytes, rows, cols, fallen))
+}
 
-    let steps = bfs(&board).expect("Should find bfs solution");
+// Adding bytes only ever removes paths, so the number of fallen bytes where the exit becomes
+// unreachable can be found by binary search. `None` if the exit stays reachable, or never was.
+fn first_blocking_byte(
+    bytes: &[Coord],
+    rows: usize,
+    cols: usize,
+) -> Result<Option<Coord>, MemoryError> {
+    check_bytes(bytes, rows, cols)?;
 
-    Some(steps)
-}
+    // Invariant: the exit is reachable after `low` bytes, and not after `high` bytes
+    let mut low = 0;
+    let mut high = bytes.len();
 
-pub fn part_two(input: &str) -> Option<String> {
-    let num_lines = input.lines().count();
-    let is_test = num_lines == 25;
-
-    // Should probably have used binary search here, but let's just brute force it to catch up on the other puzzles
-    for i in 1..num_lines {
-        let bytes_to_take = num_lines - i;
-        let lines: Vec<&str> = input.lines().take(bytes_to_take).collect();
-        let board = Board::new(lines, is_test).expect("Should parse board!");
-        let maybe_steps = bfs(&board);
-
-        if maybe_steps.is_some() {
-            let first_blocking_coord = input
-                .lines()
-                .nth(bytes_to_take)
-                .expect("Should find first blocking coord");
-            return Some(first_blocking_coord.to_string());
+    if fallen_path(bytes, rows, cols, low).is_none()
+        || fallen_path(bytes, rows, cols, high).is_some()
+    {
+        return Ok(None);
+    }
+
+    while high - low > 1 {
+        let mid = (low + high) / 2;
+        if fallen_path(bytes, rows, cols, mid).is_some() {
+            low = mid;
+        } else {
+            high = mid;
         }
     }
 
-    None
+    Ok(Some(bytes[high - 1]))
+}
+
+pub fn part_one(input: &str) -> Option<u32> {
+    let bytes = parse_bytes(input)?;
+    shortest_path(&bytes, MEMORY_SIZE, MEMORY_SIZE, PART_ONE_BYTES).ok()?
+}
+
+pub fn part_two(input: &str) -> Option<String> {
+    let bytes = parse_bytes(input)?;
+    let (x, y) = first_blocking_byte(&bytes, MEMORY_SIZE, MEMORY_SIZE).ok()??;
+    Some(format!("{},{}", x, y))
 }
 
 #[cfg(test)]
@@ -142,13 +192,51 @@ mod tests {
 
     #[test]
     fn test_part_one() {
-        let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some(22));
+        let bytes = parse_bytes(&advent_of_code::template::read_file("examples", DAY)).unwrap();
+        let result = shortest_path(&bytes, 7, 7, 12);
+        assert_eq!(result, Ok(Some(22)));
     }
 
     #[test]
     fn test_part_two() {
-        let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, Some("6,1".to_string()));
+        let bytes = parse_bytes(&advent_of_code::template::read_file("examples", DAY)).unwrap();
+        let result = first_blocking_byte(&bytes, 7, 7);
+        assert_eq!(result, Ok(Some((6, 1))));
+    }
+
+    #[test]
+    fn test_bytes_outside_memory() {
+        let bytes = parse_bytes(&advent_of_code::template::read_file("examples", DAY)).unwrap();
+        assert_eq!(
+            shortest_path(&bytes, 6, 6, 12),
+            Err(MemoryError::OutOfRange((6, 3)))
+        );
+        // Also when the byte out of range falls after the exit is blocked
+        assert_eq!(
+            first_blocking_byte(&bytes, 6, 6),
+            Err(MemoryError::OutOfRange((6, 3)))
+        );
+    }
+
+    #[test]
+    fn test_empty_memory() {
+        assert_eq!(shortest_path(&[], 0, 5, 0), Err(MemoryError::Empty));
+        assert_eq!(first_blocking_byte(&[], 5, 0), Err(MemoryError::Empty));
+        assert_eq!(shortest_path(&[], 1, 1, 0), Ok(Some(0)));
+    }
+
+    #[test]
+    fn test_exit_never_blocked() {
+        let bytes = vec![(1, 0), (1, 1)];
+        assert_eq!(first_blocking_byte(&bytes, 3, 3), Ok(None));
+        assert_eq!(shortest_path(&bytes, 3, 3, 2), Ok(Some(4)));
+    }
+
+    #[test]
+    fn test_non_square_memory() {
+        // A wall with a single gap at the bottom
+        let bytes = vec![(2, 0), (2, 1), (2, 2), (1, 3), (3, 0)];
+        assert_eq!(shortest_path(&bytes, 4, 5, 3), Ok(Some(7)));
+        assert_eq!(first_blocking_byte(&bytes, 4, 5), Ok(Some((1, 3))));
     }
 }
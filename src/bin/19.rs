@@ -1,6 +1,8 @@
-use memoize::memoize;
+use std::collections::HashMap;
 
-advent_of_code::solution!(19);
+use itertools::Itertools;
+
+advent_of_code::solution!(19, debug = debug);
 
 struct Problem {
     patterns: Vec<String>,
@@ -9,91 +11,228 @@ struct Problem {
 
 impl Problem {
     fn new(s: &str) -> Option<Problem> {
-        let patterns: Vec<String> = s
-            .split("\n\n")
-            .nth(0)?
-            .split(", ")
-            .map(|p| p.to_string())
-            .collect();
-        let designs = s
-            .split("\n\n")
-            .nth(1)?
-            .split("\n")
-            .map(|p| p.to_string())
-            .collect();
+        let (patterns, designs) = s.split_once("\n\n")?;
+        let patterns = patterns.trim().split(", ").map(|p| p.to_string()).collect();
+        let designs = designs.lines().map(|p| p.to_string()).collect();
 
         Some(Problem { patterns, designs })
     }
 }
 
-fn is_possible(design: &str, patterns: &Vec<String>) -> bool {
-    if design == "" {
-        // Base case, always possible
-        return true;
+#[derive(Default)]
+struct TrieNode {
+    children: HashMap<u8, usize>,
+    // Whether a pattern ends here
+    terminal: bool,
+}
+
+// The towel patterns, sharing common prefixes, so that every pattern that starts a design can be
+// found in a single walk down the trie
+struct TowelTrie {
+    // The root is the first node
+    nodes: Vec<TrieNode>,
+}
+
+impl TowelTrie {
+    fn new<'a>(patterns: impl IntoIterator<Item = &'a str>) -> TowelTrie {
+        let mut trie = TowelTrie {
+            nodes: vec![TrieNode::default()],
+        };
+        for pattern in patterns {
+            trie.insert(pattern);
+        }
+        trie
     }
 
-    let mut candidate_patterns = patterns
-        .iter()
-        .filter(|pattern| design.starts_with(*pattern));
-    let rest_is_possible = candidate_patterns.any(|candidate_pattern| {
-        let rest = &design[candidate_pattern.len()..];
+    fn insert(&mut self, pattern: &str) {
+        let mut node = 0;
+        for color in pattern.bytes() {
+            node = match self.nodes[node].children.get(&color) {
+                Some(child) => *child,
+                None => {
+                    self.nodes.push(TrieNode::default());
+                    let child = self.nodes.len() - 1;
+                    self.nodes[node].children.insert(color, child);
+                    child
+                }
+            };
+        }
+        self.nodes[node].terminal = true;
+    }
 
-        is_possible(rest, patterns)
-    });
-    rest_is_possible
-}
+    // Lengths of the patterns that the design starts with, shortest first
+    fn prefix_lengths<'a>(&'a self, design: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+        design
+            .iter()
+            .scan(0, |node, color| {
+                *node = *self.nodes[*node].children.get(color)?;
+                Some(*node)
+            })
+            .enumerate()
+            .filter(|(_, node)| self.nodes[*node].terminal)
+            .map(|(i, _)| i + 1)
+    }
 
-#[memoize]
-fn possible_combinations(design: String, patterns: Vec<String>) -> u64 {
-    if design == "" {
-        // Base case, always possible
-        return 1;
+    // Number of ways to arrange the patterns into the end of the design from every position. The
+    // design can be made at all when the count at the start isn't zero.
+    fn suffix_arrangements(&self, design: &str) -> Vec<u64> {
+        let design = design.as_bytes();
+        let mut counts = vec![0; design.len() + 1];
+        counts[design.len()] = 1;
+        for start in (0..design.len()).rev() {
+            counts[start] = self
+                .prefix_lengths(&design[start..])
+                .map(|len| counts[start + len])
+                .sum();
+        }
+        counts
     }
 
-    let mut candidate_patterns = patterns
-        .iter()
-        .filter(|pattern| design.starts_with(*pattern)); // Implicitly filters out everything and makes the function return 0 if branch is not possible
-    let possible_child_combinations = candidate_patterns.map(|candidate_pattern| {
-        let rest = &design[candidate_pattern.len()..];
-
-        let child_combinations = possible_combinations(rest.to_string(), patterns.clone());
-        child_combinations
-    });
-    possible_child_combinations.sum()
-}
+    fn arrangements(&self, design: &str) -> u64 {
+        self.suffix_arrangements(design)[0]
+    }
 
-pub fn part_one(input: &str) -> Option<u32> {
-    let problem = Problem::new(input).expect("Input should parse");
+    // Up to `limit` ways to arrange the patterns into the design
+    fn decompositions<'a>(&self, design: &'a str, limit: usize) -> Vec<Vec<&'a str>> {
+        let counts = self.suffix_arrangements(design);
+        let mut decompositions = vec![];
+        self.decompose(design, 0, &counts, &mut vec![], &mut decompositions, limit);
+        decompositions
+    }
 
-    // For each design, it is possible if any of the subdesigns are possible when trying every feasible start pattern
-    let num_possible: u32 = problem
-        .designs
-        .iter()
-        .filter(|design| is_possible(design, &problem.patterns))
-        .count()
-        .try_into()
-        .ok()?;
+    fn decompose<'a>(
+        &self,
+        design: &'a str,
+        start: usize,
+        counts: &[u64],
+        current: &mut Vec<&'a str>,
+        decompositions: &mut Vec<Vec<&'a str>>,
+        limit: usize,
+    ) {
+        if decompositions.len() >= limit {
+            return;
+        }
+        if start == design.len() {
+            decompositions.push(current.clone());
+            return;
+        }
+
+        for len in self.prefix_lengths(&design.as_bytes()[start..]) {
+            // Skip patterns that leave a rest that can't be made
+            if counts[start + len] == 0 {
+                continue;
+            }
+            current.push(&design[start..start + len]);
+            self.decompose(design, start + len, counts, current, decompositions, limit);
+            current.pop();
+        }
+    }
+}
 
-    Some(num_possible)
+fn arrangement_counts(input: &str) -> Option<Vec<u64>> {
+    let problem = Problem::new(input)?;
+    let trie = TowelTrie::new(problem.patterns.iter().map(String::as_str));
+
+    Some(
+        problem
+            .designs
+            .iter()
+            .map(|design| trie.arrangements(design))
+            .collect(),
+    )
 }
 
-pub fn part_two(input: &str) -> Option<u64> {
-    let problem = Problem::new(input).expect("Input should parse");
+// Arrangements listed per design in the debug report
+const DEBUG_ARRANGEMENTS: usize = 3;
+
+// Every design with its number of arrangements and the first few of them
+fn debug(input: &str) -> Option<String> {
+    let problem = Problem::new(input)?;
+    let trie = TowelTrie::new(problem.patterns.iter().map(String::as_str));
 
-    // For each design, it is possible if any of the subdesigns are possible when trying every feasible start pattern
-    let possible_combinations: u64 = problem
+    let report = problem
         .designs
         .iter()
-        .map(|design| possible_combinations(design.to_string(), problem.patterns.clone()))
-        .sum();
+        .map(|design| {
+            let arrangements = trie
+                .decompositions(design, DEBUG_ARRANGEMENTS)
+                .iter()
+                .map(|decomposition| decomposition.join(" "))
+                .join(", ");
+            format!("{design}: {} [{arrangements}]", trie.arrangements(design))
+        })
+        .join("\n");
+    Some(report)
+}
 
-    Some(possible_combinations)
+pub fn part_one(input: &str) -> Option<u32> {
+    let counts = arrangement_counts(input)?;
+    let num_possible = counts.iter().filter(|count| **count > 0).count();
+    num_possible.try_into().ok()
+}
+
+pub fn part_two(input: &str) -> Option<u64> {
+    let counts = arrangement_counts(input)?;
+    Some(counts.iter().sum())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn example_trie() -> TowelTrie {
+        TowelTrie::new(["r", "wr", "b", "g", "bwu", "rb", "gb", "br"])
+    }
+
+    #[test]
+    fn test_prefix_lengths() {
+        let trie = example_trie();
+        assert_eq!(
+            trie.prefix_lengths(b"brwrr").collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert_eq!(
+            trie.prefix_lengths(b"bwurrg").collect::<Vec<_>>(),
+            vec![1, 3]
+        );
+        assert_eq!(trie.prefix_lengths(b"ubwu").count(), 0);
+    }
+
+    #[test]
+    fn test_arrangements() {
+        let trie = example_trie();
+        assert_eq!(trie.arrangements("brwrr"), 2);
+        assert_eq!(trie.arrangements("gbbr"), 4);
+        assert_eq!(trie.arrangements("rrbgbr"), 6);
+        assert_eq!(trie.arrangements("ubwu"), 0);
+        assert_eq!(trie.arrangements("bbrgwb"), 0);
+    }
+
+    #[test]
+    fn test_decompositions() {
+        let trie = example_trie();
+        assert_eq!(
+            trie.decompositions("gbbr", 10),
+            vec![
+                vec!["g", "b", "b", "r"],
+                vec!["g", "b", "br"],
+                vec!["gb", "b", "r"],
+                vec!["gb", "br"],
+            ]
+        );
+        assert_eq!(trie.decompositions("gbbr", 1).len(), 1);
+        assert!(trie.decompositions("ubwu", 10).is_empty());
+    }
+
+    #[test]
+    fn test_debug() {
+        let report = debug(&advent_of_code::template::read_file("examples", DAY)).unwrap();
+        let lines = report.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 8);
+        assert_eq!(lines[2], "gbbr: 4 [g b b r, g b br, gb b r]");
+        assert_eq!(lines[4], "ubwu: 0 []");
+    }
+
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
@@ -1,4 +1,4 @@
-use std::{cmp::min, collections::HashMap};
+use std::collections::BTreeMap;
 
 use itertools::Itertools;
 
@@ -17,7 +17,7 @@ impl Board {
         let plants: Vec<Vec<char>> = s.lines().map(|line| line.chars().collect()).collect();
 
         let rows = s.lines().collect_vec().len();
-        let cols = s.lines().nth(0).map(|x| x.len())?;
+        let cols = s.lines().next().map(|x| x.len())?;
 
         Some(Board {
             tiles: plants,
@@ -68,14 +68,14 @@ fn coord_in_direction(board: &Board, coord: Coord, direction: Direction) -> Opti
 }
 
 fn adjacent_coords(board: &Board, coord: Coord) -> Vec<Coord> {
-    vec![
+    [
         coord_in_direction(board, coord, Direction::North),
         coord_in_direction(board, coord, Direction::East),
         coord_in_direction(board, coord, Direction::South),
         coord_in_direction(board, coord, Direction::West),
     ]
-    .iter()
-    .filter_map(|c| *c)
+    .into_iter()
+    .flatten()
     .collect()
 }
 
@@ -83,214 +83,175 @@ const START: char = 'S';
 const GOAL: char = 'E';
 const WALL: char = '#';
 
-fn baseline(board: &Board, start_coord: Coord, goal_coord: Coord) -> HashMap<Coord, u32> {
-    let mut distances: HashMap<Coord, u32> = HashMap::new();
-
-    distances.insert(start_coord, 0);
+// The track has no branches, so it can be followed from start to goal. Returns the track in order.
+fn baseline(board: &Board, start_coord: Coord, goal_coord: Coord) -> Vec<Coord> {
+    let mut track: Vec<Coord> = vec![start_coord];
 
+    let mut previous = None;
     let mut current = start_coord;
-    let mut cost: u32 = 0;
     while current != goal_coord {
-        let adjacent_coords = adjacent_coords(board, current);
-        let next = adjacent_coords
-            .iter()
-            .filter(|coord| (board.get(coord) != WALL) && !distances.contains_key(coord))
-            .next()
+        let next = adjacent_coords(board, current)
+            .into_iter()
+            .find(|coord| board.get(coord) != WALL && Some(*coord) != previous)
             .expect("Should find next"); // Should always just be a single value
-        cost += 1;
 
-        distances.insert(*next, cost);
-        current = *next;
+        track.push(next);
+        previous = Some(current);
+        current = next;
     }
 
-    distances
+    track
 }
 
-// Returns the saving of the cheat if it exists
-fn check_for_cheat(board: &Board, distances: &HashMap<Coord, u32>, coord: Coord) -> Option<u32> {
-    // Note: When doing coord_in_direction, we can safely return false for the option early, since no border coord can be part of a cheat
-    let north_coord = coord_in_direction(board, coord, Direction::North)?;
-    let south_coord = coord_in_direction(board, coord, Direction::South)?;
-    let east_coord = coord_in_direction(board, coord, Direction::East)?;
-    let west_coord = coord_in_direction(board, coord, Direction::West)?;
+fn manhattan_distance(first: Coord, second: Coord) -> usize {
+    first.0.abs_diff(second.0) + first.1.abs_diff(second.1)
+}
 
-    if let Some(cheat) = check_coords_for_cheat_part_1(board, distances, north_coord, south_coord) {
-        return Some(cheat);
-    }
+// All coordinates within the manhattan distance, including the coord itself
+fn board_coords_within_manhattan_distance(
+    board: &Board,
+    coord: Coord,
+    max_dist: usize,
+) -> impl Iterator<Item = Coord> {
+    let row_min = coord.0.saturating_sub(max_dist);
+    let row_max = (board.rows - 1).min(coord.0 + max_dist);
 
-    if let Some(cheat) = check_coords_for_cheat_part_1(board, distances, west_coord, east_coord) {
-        return Some(cheat);
-    }
+    let col_min = coord.1.saturating_sub(max_dist);
+    let col_max = (board.cols - 1).min(coord.1 + max_dist);
 
-    None
+    (row_min..=row_max)
+        .flat_map(move |row| (col_min..=col_max).map(move |col| (row, col)))
+        .filter(move |other| manhattan_distance(coord, *other) <= max_dist)
 }
 
-fn check_coords_for_cheat_part_1(
+// A cheat goes from one track tile to another through at most `max_cheat_length` steps of any
+// tiles, saving the difference between the distance along the track and the steps taken.
+// Returns how many cheats there are for every saving of at least `min_saving`.
+fn cheat_savings(
     board: &Board,
-    distances: &HashMap<(usize, usize), u32>,
-    first: (usize, usize),
-    second: (usize, usize),
-) -> Option<u32> {
-    let first_tile = board.get(&first);
-    let second_tile = board.get(&second);
-    if first_tile != WALL && second_tile != WALL {
-        if let (Some(first_cost), Some(second_cost)) =
-            (distances.get(&first), distances.get(&second))
-        {
-            // Since a cheat involves spending one step inside the wall, we
-            let diff: i32 = first_cost.abs_diff(*second_cost).try_into().ok()?;
-            let savings_potential = diff - 2;
-            if savings_potential > 0 {
-                return Some(savings_potential.try_into().ok()?);
+    track: &[Coord],
+    max_cheat_length: usize,
+    min_saving: usize,
+) -> BTreeMap<usize, usize> {
+    let mut distances: Vec<Vec<Option<usize>>> = vec![vec![None; board.cols]; board.rows];
+    for (distance, coord) in track.iter().enumerate() {
+        distances[coord.0][coord.1] = Some(distance);
+    }
+
+    let mut histogram: BTreeMap<usize, usize> = BTreeMap::new();
+    for (start_distance, start) in track.iter().enumerate() {
+        for end in board_coords_within_manhattan_distance(board, *start, max_cheat_length) {
+            let Some(end_distance) = distances[end.0][end.1] else {
+                continue;
+            };
+
+            // Only count cheats that move forward along the track, which also counts every cheat once
+            let cheat_length = manhattan_distance(*start, end);
+            let saving = end_distance.saturating_sub(start_distance + cheat_length);
+            if saving > 0 && saving >= min_saving {
+                *histogram.entry(saving).or_insert(0) += 1;
             }
         }
     }
-    None
+
+    histogram
 }
 
-pub fn part_one(input: &str) -> Option<usize> {
+fn count_cheats(input: &str, max_cheat_length: usize, min_saving: usize) -> Option<usize> {
     let board = Board::new(input)?;
-    // Note: There are no branches in the track
-    // Traverse and keep track of the cost per tile
     let start_coord = find_needle_or_fail(&board, START);
     let goal_coord = find_needle_or_fail(&board, GOAL);
 
-    let distances = baseline(&board, start_coord, goal_coord);
-
-    let cheat_wall_candidate_coords: Vec<Coord> = board
-        .tiles
-        .iter()
-        .enumerate()
-        .flat_map(|(i, row)| {
-            row.iter()
-                .enumerate()
-                .filter(|(_, tile)| **tile == WALL)
-                .map(move |(j, _)| (i, j))
-        })
-        .collect();
-
-    // Then, for each wall in the board, check
-    // - left + right
-    // - top + bottom
-    // For each wall, only one of these can be a valid cheat.
-    let cheats: Vec<u32> = cheat_wall_candidate_coords
-        .iter()
-        .map(|coord| check_for_cheat(&board, &distances, *coord))
-        .filter_map(|x| x)
-        .collect();
+    let track = baseline(&board, start_coord, goal_coord);
 
-    let cheat_over_threshold_count = cheats.iter().filter(|cheat| **cheat >= 100).count();
-
-    Some(cheat_over_threshold_count)
+    let histogram = cheat_savings(&board, &track, max_cheat_length, min_saving);
+    Some(histogram.values().sum())
 }
 
-fn manhattan_distance(first: Coord, second: Coord) -> usize {
-    first.0.abs_diff(second.0) + first.1.abs_diff(second.1)
-}
-
-fn non_wall_board_coords_within_manhattan_distance(
-    board: &Board,
-    coord: Coord,
-    max_dist: usize,
-) -> Vec<Coord> {
-    // Stupid/simple solution, instead of making some clever for-loops (which would be more efficient), just try all coords within a square and check
-    // if the manhattan distance is within max_dist
-    // Note: this also includes the coord itself, but we will implicitly filter that out later
-    let row_min = coord.0.checked_sub(max_dist).unwrap_or(0);
-    let row_max = min(board.rows - 1, coord.0 + max_dist); // Not inclusive
-
-    let col_min = coord.1.checked_sub(max_dist).unwrap_or(0);
-    let col_max = min(board.cols - 1, coord.1 + max_dist); // Not inclusive
-
-    (row_min..row_max+1)
-        .flat_map(|row| (col_min..col_max + 1).map(move |col| (row, col)))
-        .filter(|other| manhattan_distance(coord, *other) <= max_dist)
-        .collect()
+pub fn part_one(input: &str) -> Option<usize> {
+    count_cheats(input, 2, 100)
 }
 
 pub fn part_two(input: &str) -> Option<usize> {
-    let board = Board::new(input)?;
-    // Note: There are no branches in the track
-    // Traverse and keep track of the cost per tile
-    let start_coord = find_needle_or_fail(&board, START);
-    let goal_coord = find_needle_or_fail(&board, GOAL);
-
-    let unsigned_distances = baseline(&board, start_coord, goal_coord);
-    let distances: HashMap<Coord, i32> = unsigned_distances
-        .clone()
-        .into_iter()
-        .map(|(k, v)| (k, i32::try_from(v).expect("Should convert u32 to i32")))
-        .collect();
-
-    // Do a similar approach this time as well, but instead of checking each cross (north/south) and (west/east),
-    // we now take each non-wall tile within a manhattan distance of 20 and consider it a potential end of the cheat.
-    // Then, we do a similar thing as the first part,
-    // We take the other coord, and check for distance[other] - distance[candidate] > manhattan_distance(candidate, other)
-
-    let non_wall_candidate_coords: Vec<Coord> = board
-        .tiles
-        .iter()
-        .enumerate()
-        .flat_map(|(i, row)| {
-            row.iter()
-                .enumerate()
-                .filter(|(_, tile)| **tile != WALL)
-                .map(move |(j, _)| (i, j))
-        })
-        .collect();
-
-    let cheats: Vec<i32> = non_wall_candidate_coords
-        .iter()
-        .flat_map(|coord| {
-            let end_candidates =
-                non_wall_board_coords_within_manhattan_distance(&board, *coord, 20);
-            end_candidates
-                .iter()
-                .filter_map(|candidate| {
-                    if let (Some(coord_dist), Some(candidate_dist)) =
-                        (distances.get(candidate), distances.get(coord))
-                    {
-                        let manhattan_dist: i32 = manhattan_distance(*coord, *candidate)
-                            .try_into()
-                            .expect("Should convert manhattan dist");
-                        let dist_diff = candidate_dist - coord_dist;
-                        let potential_cheat = dist_diff - manhattan_dist;
-                        if potential_cheat > 0 {
-                            Some(potential_cheat)
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
-                    }
-                })
-                .collect_vec()
-        })
-        .collect();
-
-    // println!("cheats: {:?}", cheats);
-    // 952515 too low
-    // 1006932 too high
-
-    let cheat_over_threshold_count = cheats.iter().filter(|cheat| **cheat >= 100).count();
-
-    Some(cheat_over_threshold_count)
+    count_cheats(input, 20, 100)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn example_histogram(max_cheat_length: usize, min_saving: usize) -> Vec<(usize, usize)> {
+        let board = Board::new(&advent_of_code::template::read_file("examples", DAY)).unwrap();
+        let start_coord = find_needle_or_fail(&board, START);
+        let goal_coord = find_needle_or_fail(&board, GOAL);
+        let track = baseline(&board, start_coord, goal_coord);
+
+        // (count, saving), like in the puzzle text
+        cheat_savings(&board, &track, max_cheat_length, min_saving)
+            .into_iter()
+            .map(|(saving, count)| (count, saving))
+            .collect()
+    }
+
+    #[test]
+    fn test_baseline() {
+        let board = Board::new(&advent_of_code::template::read_file("examples", DAY)).unwrap();
+        let start_coord = find_needle_or_fail(&board, START);
+        let goal_coord = find_needle_or_fail(&board, GOAL);
+        let track = baseline(&board, start_coord, goal_coord);
+        assert_eq!(track.len() - 1, 84);
+    }
+
+    #[test]
+    fn test_savings_part_one() {
+        let result = example_histogram(2, 1);
+        let expected = vec![
+            (14, 2),
+            (14, 4),
+            (2, 6),
+            (4, 8),
+            (2, 10),
+            (3, 12),
+            (1, 20),
+            (1, 36),
+            (1, 38),
+            (1, 40),
+            (1, 64),
+        ];
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_savings_part_two() {
+        let result = example_histogram(20, 50);
+        let expected = vec![
+            (32, 50),
+            (31, 52),
+            (29, 54),
+            (39, 56),
+            (25, 58),
+            (23, 60),
+            (20, 62),
+            (19, 64),
+            (12, 66),
+            (14, 68),
+            (12, 70),
+            (22, 72),
+            (4, 74),
+            (3, 76),
+        ];
+        assert_eq!(result, expected);
+    }
+
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
         assert_eq!(result, Some(0));
-        // assert_eq!(result, None);
     }
 
     #[test]
     fn test_part_two() {
         let result = part_two(&advent_of_code::template::read_file("examples", DAY));
-        assert_eq!(result, None);
+        assert_eq!(result, Some(0));
     }
 }
@@ -1,159 +1,148 @@
-use std::{collections::HashMap, iter::zip};
-
 use itertools::Itertools;
+use rayon::prelude::*;
 
 advent_of_code::solution!(22);
 
-fn mix(given: u64, secret: u64) -> u64 {
-    given ^ secret
-}
+const STEPS: usize = 2000;
+
+const PRUNE_MASK: u64 = 16777216 - 1;
 
-fn prune(secret: u64) -> u64 {
-    secret % 16777216
+// Changes are in -9..=9, so the last four changes are a four digit number in base 19
+const CHANGE_BASE: usize = 19;
+const SEQUENCES: usize = CHANGE_BASE.pow(4);
+
+// The secret generator. Multiplying and dividing by powers of two are shifts, mixing is xor and
+// pruning keeps the lowest 24 bits, so this is cheap to run on many secrets at once.
+pub fn next_secret(secret: u64) -> u64 {
+    let secret = ((secret << 6) ^ secret) & PRUNE_MASK;
+    let secret = ((secret >> 5) ^ secret) & PRUNE_MASK;
+    ((secret << 11) ^ secret) & PRUNE_MASK
 }
 
-fn next_secret(secret: u64) -> u64 {
-    // Calculate the result of multiplying the secret number by 64.
-    // Then, mix this result into the secret number. Finally, prune the secret number.
-    let res1 = secret * 64;
-    let res2 = mix(res1, secret);
-    let res3 = prune(res2);
-
-    // Calculate the result of dividing the secret number by 32.
-    // Round the result down to the nearest integer.
-    // Then, mix this result into the secret number. Finally, prune the secret number.
-    let res4 = res3 / 32;
-    let res5 = prune(mix(res4, res3));
-
-    // Calculate the result of multiplying the secret number by 2048.
-    // Then, mix this result into the secret number. Finally, prune the secret number.
-    let res6 = res5 * 2048;
-    prune(mix(res6, res5))
+// Steps every secret once. Written as a plain loop over a slice so it can be vectorized.
+pub fn next_secrets(secrets: &mut [u64]) {
+    for secret in secrets.iter_mut() {
+        *secret = next_secret(*secret);
+    }
 }
 
 fn price(secret: u64) -> u64 {
     secret % 10
 }
 
+fn parse_secrets(input: &str) -> Option<Vec<u64>> {
+    input.lines().map(|x| x.parse().ok()).collect()
+}
+
 pub fn part_one(input: &str) -> Option<u64> {
-    let steps = 2000;
-    let initial_secrets: Vec<u64> = input
-        .lines()
-        .map(|x| x.parse().ok())
-        .collect::<Option<Vec<_>>>()?;
-    let final_secrets = initial_secrets.iter().map(|initial_secret| {
-        let mut secret = *initial_secret;
-        for _ in 0..steps {
-            secret = next_secret(secret);
-        }
-        secret
-    });
-    let secret_sum: u64 = final_secrets.sum();
-    Some(secret_sum)
+    let mut secrets = parse_secrets(input)?;
+    for _ in 0..STEPS {
+        next_secrets(&mut secrets);
+    }
+    Some(secrets.iter().sum())
 }
 
-type Bid = (i64, i64, i64, i64);
-
-pub fn part_two(input: &str) -> Option<i64> {
-    // TODO
-    let steps = 2000;
-    let initial_secrets: Vec<u64> = input
-        .lines()
-        .map(|x| x.parse().ok())
-        .collect::<Option<Vec<_>>>()?;
-    // Simulate the secrets of each monkey for 2000 steps
-    let all_secrets: Vec<Vec<u64>> = initial_secrets
-        .iter()
-        .map(|initial_secret| {
-            let mut secrets = vec![];
-            let mut secret = *initial_secret;
-            secrets.push(secret);
-            for _ in 0..steps {
-                secret = next_secret(secret);
-                secrets.push(secret);
-            }
-            secrets
-        })
-        .collect();
-
-    // Calculate the prices from the secrets
-    let all_prices: Vec<Vec<i64>> = all_secrets
-        .iter()
-        .map(|monkey_secrets| {
-            monkey_secrets
-                .iter()
-                .map(|secret| {
-                    let unsigned_price = price(*secret);
-                    let signed_price: i64 =
-                        unsigned_price.try_into().expect("Should convert price");
-                    signed_price
-                })
-                .collect()
-        })
-        .collect();
-    // Calculate the diffs from the prices
-    let price_changes: Vec<Vec<i64>> = all_prices
-        .iter()
-        .map(|monkey_prices| {
-            monkey_prices
-                .iter()
-                .tuple_windows::<(_, _)>()
-                .map(|(first, second)| second - first)
-                .collect()
-        })
-        .collect();
-
-    // Calculate all the bids and prices (i64, i64, i64, i64), i64 for each monkey
-    let bids: Vec<Vec<Bid>> = price_changes
-        .iter()
-        .map(|monkey_price_changes| {
-            monkey_price_changes
-                .iter()
-                .cloned()
-                .tuple_windows::<(_, _, _, _)>()
-                .collect()
-        })
-        .collect();
-
-    let bid_prices: Vec<Vec<i64>> = all_prices
-        .iter()
-        .map(|monkey_prices| monkey_prices.as_slice()[4..].to_vec())
-        .collect();
-    // -> How do we do this?
-    // For each monkey, take the first price for each unique bid -> HashMap
-    let mut first_bids: Vec<HashMap<Bid, i64>> = vec![];
-    for (monkey_bids, monkey_prices) in zip(bids, bid_prices) {
-        let mut monkey_first_bids: HashMap<Bid, i64> = HashMap::new();
-        for (bid, price) in zip(monkey_bids, monkey_prices) {
-            if !monkey_first_bids.contains_key(&bid) {
-                monkey_first_bids.insert(bid, price);
-            }
+// Adds the price a buyer sells at for every sequence of four changes to the totals. A buyer sells
+// the first time a sequence appears, so `seen` is a bitmap of the sequences already passed.
+fn add_buyer_bananas(initial_secret: u64, steps: usize, totals: &mut [u32], seen: &mut [u64]) {
+    seen.fill(0);
+
+    let mut secret = initial_secret;
+    let mut previous_price = price(secret);
+    let mut sequence = 0;
+    for step in 0..steps {
+        secret = next_secret(secret);
+        let current_price = price(secret);
+        let change = (current_price + 9 - previous_price) as usize;
+        sequence = (sequence * CHANGE_BASE + change) % SEQUENCES;
+        previous_price = current_price;
+
+        if step < 3 {
+            continue;
+        }
+
+        let (word, bit) = (sequence / 64, 1 << (sequence % 64));
+        if seen[word] & bit == 0 {
+            seen[word] |= bit;
+            totals[sequence] += current_price as u32;
         }
-        first_bids.push(monkey_first_bids);
     }
+}
 
-    let all_bids_and_prices: Vec<(Bid, i64)> = first_bids
-        .clone()
-        .into_iter()
-        .flat_map(|monkey_first_bids| monkey_first_bids)
-        .collect();
+fn empty_totals() -> (Vec<u32>, Vec<u64>) {
+    (vec![0; SEQUENCES], vec![0; SEQUENCES.div_ceil(64)])
+}
 
-    // For each bid, sum all the first prices
-    let bananas_by_bid = all_bids_and_prices
-        .clone()
-        .into_iter()
-        .into_grouping_map()
-        .reduce(|price_acc, _, price| price_acc + price);
+// The bananas earned for every sequence of four changes, summed over all buyers
+fn banana_totals(initial_secrets: &[u64], steps: usize, parallel: bool) -> Vec<u32> {
+    if !parallel {
+        let (mut totals, mut seen) = empty_totals();
+        for secret in initial_secrets {
+            add_buyer_bananas(*secret, steps, &mut totals, &mut seen);
+        }
+        return totals;
+    }
 
-    let max_bananas = *bananas_by_bid.values().max()?;
+    initial_secrets
+        .par_iter()
+        .fold(empty_totals, |(mut totals, mut seen), secret| {
+            add_buyer_bananas(*secret, steps, &mut totals, &mut seen);
+            (totals, seen)
+        })
+        .map(|(totals, _)| totals)
+        .reduce(
+            || vec![0; SEQUENCES],
+            |first, second| {
+                first
+                    .into_iter()
+                    .zip_eq(second)
+                    .map(|(a, b)| a + b)
+                    .collect()
+            },
+        )
+}
 
-    Some(max_bananas)
+fn most_bananas(input: &str, parallel: bool) -> Option<u32> {
+    let initial_secrets = parse_secrets(input)?;
+    let totals = banana_totals(&initial_secrets, STEPS, parallel);
+    totals.into_iter().max()
+}
+
+pub fn part_two(input: &str) -> Option<u32> {
+    most_bananas(input, true)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn sequence_index(changes: [i64; 4]) -> usize {
+        changes
+            .iter()
+            .fold(0, |acc, change| acc * CHANGE_BASE + (change + 9) as usize)
+    }
+
+    #[test]
+    fn test_next_secret() {
+        let secrets = std::iter::successors(Some(123), |secret| Some(next_secret(*secret)))
+            .skip(1)
+            .take(10)
+            .collect_vec();
+        let expected = vec![
+            15887950, 16495136, 527345, 704524, 1553684, 12683156, 11100544, 12249484, 7753432,
+            5908254,
+        ];
+        assert_eq!(secrets, expected);
+    }
+
+    #[test]
+    fn test_next_secrets() {
+        let mut secrets = vec![1, 10, 100, 2024];
+        next_secrets(&mut secrets);
+        let expected = [1, 10, 100, 2024].map(next_secret).to_vec();
+        assert_eq!(secrets, expected);
+    }
+
     #[test]
     fn test_part_one() {
         let result = part_one(&advent_of_code::template::read_file("examples", DAY));
@@ -162,7 +151,30 @@ mod tests {
 
     #[test]
     fn test_part_two() {
-        let result = part_two(&advent_of_code::template::read_file_part("examples", DAY, 2));
+        let result = part_two(&advent_of_code::template::read_file_part(
+            "examples", DAY, 2,
+        ));
         assert_eq!(result, Some(23));
     }
+
+    #[test]
+    fn test_sequential_matches_parallel() {
+        let input = advent_of_code::template::read_file_part("examples", DAY, 2);
+        assert_eq!(most_bananas(&input, false), Some(23));
+        assert_eq!(most_bananas(&input, true), Some(23));
+    }
+
+    #[test]
+    fn test_first_sale_only() {
+        // Prices for 123 are 3, 0, 6, 5, 4, 4, 6, 4, 4, 2
+        let totals = banana_totals(&[123], 9, false);
+        assert_eq!(totals[sequence_index([-1, -1, 0, 2])], 6);
+        assert_eq!(totals[sequence_index([-3, 6, -1, -1])], 4);
+        assert_eq!(totals.iter().max(), Some(&6));
+
+        // The example sequence is sold once per buyer, to a total of 7 + 7 + 9
+        let input = advent_of_code::template::read_file_part("examples", DAY, 2);
+        let totals = banana_totals(&parse_secrets(&input).unwrap(), STEPS, false);
+        assert_eq!(totals[sequence_index([-2, 1, -1, 3])], 23);
+    }
 }
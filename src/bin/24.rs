@@ -8,9 +8,9 @@ use regex::Regex;
 
 #[derive(Clone, Debug)]
 enum Operator {
-    XOR,
-    AND,
-    OR,
+    Xor,
+    And,
+    Or,
 }
 
 #[derive(Clone, Debug)]
@@ -26,11 +26,15 @@ fn digits(label: &str) -> Option<u32> {
     caps[1].parse().ok()
 }
 
+fn is_output_wire(wire: &str) -> bool {
+    digits(wire).is_some()
+}
+
 fn simulate(a: bool, b: bool, operator: Operator) -> bool {
     match operator {
-        Operator::XOR => (a || b) && !(a && b),
-        Operator::AND => a && b,
-        Operator::OR => a || b,
+        Operator::Xor => (a || b) && !(a && b),
+        Operator::And => a && b,
+        Operator::Or => a || b,
     }
 }
 
@@ -40,6 +44,7 @@ lazy_static! {
     static ref gate_pattern: Regex =
         Regex::new(r"([a-z0-9]+) (AND|OR|XOR) ([a-z0-9]+) -> ([a-z0-9]+)").unwrap();
     static ref digit_pattern: Regex = Regex::new(r"^z(\d\d)$").unwrap();
+    static ref input_pattern: Regex = Regex::new(r"^[xy]\d+$").unwrap();
 }
 
 impl Gate {
@@ -47,9 +52,9 @@ impl Gate {
         let caps = gate_pattern.captures(s)?;
 
         let operator = match &caps[2] {
-            "AND" => Operator::AND,
-            "XOR" => Operator::XOR,
-            "OR" => Operator::OR,
+            "AND" => Operator::And,
+            "XOR" => Operator::Xor,
+            "OR" => Operator::Or,
             _ => {
                 return None;
             }
@@ -86,32 +91,33 @@ fn find_value(
     simulate(a, b, gate.operator)
 }
 
+// Swapping outputs can introduce loops, so wires that depend on themselves evaluate to None
 fn find_value_part_2(
-    gate: Gate,
+    wire: &str,
     all_gates: &HashMap<String, Gate>,
     given_values: &HashMap<String, bool>,
-) -> bool {
-    let a = if let Some(a_value) = given_values.get(&gate.in_a) {
-        *a_value
-    } else {
-        if let Some(a_gate) = all_gates.get(&gate.in_a) {
-            find_value_part_2(a_gate.clone(), all_gates, given_values)
-        } else {
-            false
-        }
+    cache: &mut HashMap<String, Option<bool>>,
+) -> Option<bool> {
+    if let Some(value) = given_values.get(wire) {
+        return Some(*value);
+    }
+    if let Some(value) = cache.get(wire) {
+        return *value;
+    }
+    let Some(gate) = all_gates.get(wire) else {
+        return Some(false);
     };
 
-    let b = if let Some(b_value) = given_values.get(&gate.in_b) {
-        *b_value
-    } else {
-        if let Some(b_gate) = all_gates.get(&gate.in_b) {
-            find_value_part_2(b_gate.clone(), all_gates, given_values)
-        } else {
-            false
-        }
-    };
+    // Mark as in progress, so that a loop back to this wire is detected
+    cache.insert(wire.to_string(), None);
 
-    simulate(a, b, gate.operator)
+    let value = find_value_part_2(&gate.in_a, all_gates, given_values, cache).and_then(|a| {
+        let b = find_value_part_2(&gate.in_b, all_gates, given_values, cache)?;
+        Some(simulate(a, b, gate.operator.clone()))
+    });
+
+    cache.insert(wire.to_string(), value);
+    value
 }
 
 pub fn part_one(input: &str) -> Option<u64> {
@@ -124,7 +130,7 @@ pub fn part_one(input: &str) -> Option<u64> {
     // let given_values: Vec<(String, bool)> = input
     let given_values: HashMap<String, bool> = input
         .split("\n\n")
-        .nth(0)?
+        .next()?
         .lines()
         .map(|line| {
             let wire = line.split(": ").collect_vec()[0];
@@ -133,17 +139,7 @@ pub fn part_one(input: &str) -> Option<u64> {
         })
         .collect();
 
-    let mut known_values: HashMap<String, bool> = given_values.clone().into_iter().collect();
-
-    // Make a map of wires and their known values
-    let gates = input
-        .split("\n\n")
-        .nth(1)?
-        .lines()
-        .map(|line| Gate::new(line))
-        .collect::<Option<Vec<Gate>>>()?;
-
-    // dbg!(gates.clone());
+    let gates = parse_gates(input)?;
 
     let all_gates: HashMap<String, Gate> = gates
         .iter()
@@ -152,7 +148,7 @@ pub fn part_one(input: &str) -> Option<u64> {
 
     let z_values: HashMap<String, bool> = gates
         .iter()
-        .filter(|gate| gate.out.starts_with("z"))
+        .filter(|gate| is_output_wire(&gate.out))
         .cloned()
         .map(|gate| {
             (
@@ -165,7 +161,7 @@ pub fn part_one(input: &str) -> Option<u64> {
     let output: u64 = z_values
         .iter()
         .filter(|(_, value)| **value)
-        .map(|(label, gate)| {
+        .map(|(label, _)| {
             let d = digits(label).expect("Should find digits");
             2u64.pow(d)
         })
@@ -174,27 +170,21 @@ pub fn part_one(input: &str) -> Option<u64> {
     Some(output)
 }
 
-fn with_output(gate: Gate, out: &str) -> Gate {
-    let mut new_gate = gate.clone();
-    new_gate.out = out.to_string();
-    new_gate
+fn gates_by_output(gates: &[Gate]) -> HashMap<String, Gate> {
+    gates
+        .iter()
+        .map(|gate| (gate.out.clone(), gate.clone()))
+        .collect()
 }
 
-fn apply_overrides(gate: Gate) -> Gate {
-    match gate.out.as_str() {
-        "z06" => with_output(gate, "vwr"),
-        "vwr" => with_output(gate, "z06"),
-
-        "z11" => with_output(gate, "tqm"),
-        "tqm" => with_output(gate, "z11"),
-
-        "z16" => with_output(gate, "kfs"),
-        "kfs" => with_output(gate, "z16"),
-
-        "gfv" => with_output(gate, "hcm"),
-        "hcm" => with_output(gate, "gfv"),
-
-        _ => gate,
+// Swaps the output wires of the gates driving each pair. Swapping the same pairs again
+// restores the circuit.
+fn swap_outputs(all_gates: &mut HashMap<String, Gate>, swaps: &[(&str, &str)]) {
+    for (a, b) in swaps {
+        if let [Some(gate_a), Some(gate_b)] = all_gates.get_disjoint_mut([*a, *b]) {
+            std::mem::swap(gate_a, gate_b);
+            std::mem::swap(&mut gate_a.out, &mut gate_b.out);
+        }
     }
 }
 
@@ -203,7 +193,7 @@ fn u64_to_bits(num: u64) -> Vec<bool> {
     let mut x = num;
     while x != 0 {
         out.push(x % 2 == 1);
-        x = x / 2;
+        x /= 2;
     }
     out
 }
@@ -218,114 +208,167 @@ fn bits_to_pairs(bits: Vec<bool>, prefix: &str) -> Vec<(String, bool)> {
         .collect()
 }
 
-fn simulate_part_2(input: &str, x_value: u64, y_value: u64) -> Option<u64> {
-    // let x_value = 2u64.pow(45) - 1;
-    let x_bits = u64_to_bits(x_value);
-    let x_given_values = bits_to_pairs(x_bits.clone(), "x");
-
-    // let y_value =  1;
-    let y_bits = u64_to_bits(y_value);
-    let y_given_values = bits_to_pairs(y_bits.clone(), "y");
-
-    // let mut known_values: HashMap<String, bool> = x_given_values.clone().into_iter().chain(y_given_values.clone().into_iter()).collect();
-    let given_values: HashMap<String, bool> = x_given_values
-        .clone()
-        .into_iter()
-        .chain(y_given_values.clone().into_iter())
-        .collect();
-
-    // Make a map of wires and their known values
-    let raw_gates = input
+fn parse_gates(input: &str) -> Option<Vec<Gate>> {
+    input
         .split("\n\n")
         .nth(1)?
         .lines()
-        .map(|line| Gate::new(line))
-        .collect::<Option<Vec<Gate>>>()?;
+        .map(Gate::new)
+        .collect::<Option<Vec<Gate>>>()
+}
 
-    let gates = raw_gates
-        .iter()
-        .map(|gate| apply_overrides(gate.clone()))
-        .collect_vec();
+// Adds x and y with the circuit
+fn simulate_part_2(all_gates: &HashMap<String, Gate>, x_value: u64, y_value: u64) -> Option<u64> {
+    let x_given_values = bits_to_pairs(u64_to_bits(x_value), "x");
+    let y_given_values = bits_to_pairs(u64_to_bits(y_value), "y");
 
-    // dbg!(gates.clone());
+    let given_values: HashMap<String, bool> =
+        x_given_values.into_iter().chain(y_given_values).collect();
 
-    let all_gates: HashMap<String, Gate> = gates
-        .iter()
-        .map(|gate| (gate.out.clone(), gate.clone()))
-        .collect();
+    let mut cache = HashMap::new();
+    let mut output = 0;
+    for wire in all_gates.keys().filter(|wire| is_output_wire(wire)) {
+        if find_value_part_2(wire, all_gates, &given_values, &mut cache)? {
+            output += 2u64.pow(digits(wire)?);
+        }
+    }
 
-    let z_values: HashMap<String, bool> = gates
-        .iter()
-        .filter(|gate| gate.out.starts_with("z"))
-        .cloned()
-        .map(|gate| {
-            (
-                gate.out.clone(),
-                find_value_part_2(gate, &all_gates, &given_values),
-            )
-        })
-        .collect();
+    Some(output)
+}
 
-    let output: u64 = z_values
-        .iter()
-        .filter(|(_, value)| **value)
-        .map(|(label, gate)| {
-            let d = digits(label).expect("Should find digits");
-            2u64.pow(d)
-        })
-        .sum();
+fn input_bits(all_gates: &HashMap<String, Gate>) -> u32 {
+    all_gates
+        .values()
+        .flat_map(|gate| [&gate.in_a, &gate.in_b])
+        .filter(|wire| wire.starts_with('x') && is_input_wire(wire))
+        .unique()
+        .count() as u32
+}
+
+// Whether the circuit adds correctly once the given pairs of output wires are swapped. The
+// swaps are undone before returning.
+fn adds_correctly(all_gates: &mut HashMap<String, Gate>, swaps: &[(&str, &str)]) -> bool {
+    let bits = input_bits(all_gates);
+    let all_ones = 2u64.pow(bits) - 1;
 
-    println!("x : {:#048b}", x_value);
-    println!("y : {:#048b}", y_value);
-    println!("z': {:#048b}", output);
-    println!("z : {:#048b}", x_value + y_value);
-    println!("");
+    swap_outputs(all_gates, swaps);
+    let correct = (0..bits)
+        .flat_map(|i| [(1 << i, 0), (0, 1 << i), (1 << i, 1 << i)])
+        .chain([(all_ones, 1), (all_ones, all_ones)])
+        .all(|(x, y)| simulate_part_2(all_gates, x, y) == Some(x + y));
+    swap_outputs(all_gates, swaps);
 
-    // let actual_z_bits = u64_to_bits(output);
-    // let expected_z_bits = u64_to_bits(x_value + y_value);
+    correct
+}
 
-    // println!("x_bits: {:?}", x_bits);
-    // println!("y_bits: {:?}", y_bits);
-    // println!("z act:  {:?}", actual_z_bits);
-    // println!("z gt:   {:?}", expected_z_bits);
+// All ways of splitting the wires into pairs
+fn pairings<'a>(wires: &[&'a str]) -> Vec<Vec<(&'a str, &'a str)>> {
+    let Some((first, rest)) = wires.split_first() else {
+        return vec![vec![]];
+    };
 
-    None
+    (0..rest.len())
+        .flat_map(|i| {
+            let remaining = rest
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, wire)| *wire)
+                .collect_vec();
+            pairings(&remaining).into_iter().map(move |mut pairs| {
+                pairs.push((first, rest[i]));
+                pairs
+            })
+        })
+        .collect()
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
-    // let x_value = 2u64.pow(45) - 1;
-    // let y_value =  1;
-    let pairs: Vec<(u64, u64)> = vec![
-        (2u64.pow(45) - 1, 1),
-        (2u64.pow(40) - 1, 1),
-        (2u64.pow(6) - 1, 1),
-        (2u64.pow(11) - 1, 1),
-        (2u64.pow(16) - 1, 1),
-    ];
-
-    for (x_value, y_value) in pairs {
-        // simulate_part_2(raw_gates, x_value, y_value);
-        simulate_part_2(input, x_value, y_value);
-    }
+// Intermediate wires may have any name, including ones starting with x or y
+fn is_input_wire(wire: &str) -> bool {
+    input_pattern.is_match(wire)
+}
 
-    None
+fn is_first_input_wire(wire: &str) -> bool {
+    wire == "x00" || wire == "y00"
+}
 
-    // Last gate
-    // y37 XOR x37 -> fcw - this value - seems good?
-    // x36 AND y36 -> hcm - carry
-    // thv AND hcm -> wpj - thv should have been the previous AND value
-    // gfv OR wpj -> jgk
-    // fcw XOR jgk -> z37
+// The circuit is supposed to be a ripple-carry adder, where each bit i > 0 is a full adder:
+//
+// xi XOR yi -> a      (half sum)
+// xi AND yi -> b      (half carry)
+// a XOR carry_in -> zi
+// a AND carry_in -> c
+// b OR c -> carry_out
+//
+// Bit 0 is a half adder (x00 XOR y00 -> z00, x00 AND y00 -> carry), and the highest z
+// is the final carry out. Any gate whose output doesn't fit into this structure must have
+// had its output swapped.
+fn find_swapped_wires(gates: &[Gate]) -> Vec<String> {
+    let last_z = gates
+        .iter()
+        .map(|gate| gate.out.as_str())
+        .filter(|out| is_output_wire(out))
+        .max()
+        .unwrap_or_default();
+
+    let consumers = |wire: &str| {
+        gates
+            .iter()
+            .filter(move |gate| gate.in_a == wire || gate.in_b == wire)
+            .map(|gate| gate.operator.clone())
+            .collect_vec()
+    };
 
-    // gfv and hcm are swapped!
+    gates
+        .iter()
+        .filter(|gate| {
+            let from_inputs = is_input_wire(&gate.in_a) && is_input_wire(&gate.in_b);
+            let from_first_inputs =
+                is_first_input_wire(&gate.in_a) && is_first_input_wire(&gate.in_b);
+            let to_z = is_output_wire(&gate.out);
+
+            match gate.operator {
+                // Sums go to z, except the half sums which go to the next XOR and AND
+                Operator::Xor if from_first_inputs => gate.out != "z00",
+                Operator::Xor if from_inputs => {
+                    to_z || !consumers(&gate.out)
+                        .iter()
+                        .any(|op| matches!(op, Operator::Xor))
+                }
+                Operator::Xor => !to_z,
+                // Carries out of bit 0 go directly into bit 1
+                Operator::And if from_first_inputs => to_z,
+                // Both partial carries of a full adder are combined with an OR
+                Operator::And => {
+                    to_z || consumers(&gate.out)
+                        .iter()
+                        .any(|op| !matches!(op, Operator::Or))
+                }
+                // Carries either go to the next bit or are the final output bit
+                Operator::Or => to_z && gate.out != last_z,
+            }
+        })
+        .map(|gate| gate.out.clone())
+        .sorted()
+        .collect()
+}
+
+pub fn part_two(input: &str) -> Option<String> {
+    let gates = parse_gates(input)?;
+    let swapped = find_swapped_wires(&gates);
 
-    // jgk AND fcw -> qbd
-    // qbg OR qbd -> vgg
+    // The puzzle states that exactly four pairs of gates have been swapped
+    if swapped.len() != 8 {
+        return None;
+    }
 
-    // y36 XOR x36 -> gfv - value 36 before prev carry
-    // y35 AND x35 -> jgm
-    // thv XOR hcm -> z36
-    // hmg OR jgm -> thv
+    // Make sure that swapping the outputs back actually yields a working adder
+    let mut all_gates = gates_by_output(&gates);
+    let wires = swapped.iter().map(String::as_str).collect_vec();
+    pairings(&wires)
+        .iter()
+        .any(|swaps| adds_correctly(&mut all_gates, swaps))
+        .then(|| swapped.join(","))
 }
 
 #[cfg(test)]
@@ -355,4 +398,103 @@ mod tests {
         ));
         assert_eq!(result, None);
     }
+
+    // Builds a ripple-carry adder with the given pairs of output wires swapped
+    fn adder_input(bits: usize, swaps: &[(&str, &str)]) -> String {
+        let given = (0..bits)
+            .flat_map(|i| [format!("x{:02}: 0", i), format!("y{:02}: 0", i)])
+            .join("\n");
+
+        let carry = |i: usize| {
+            if i == bits - 1 {
+                format!("z{:02}", bits)
+            } else {
+                format!("c{:02}", i)
+            }
+        };
+
+        let mut gates = vec![
+            format!("x00 XOR y00 -> z00"),
+            format!("y00 AND x00 -> {}", carry(0)),
+        ];
+        for i in 1..bits {
+            gates.push(format!("x{i:02} XOR y{i:02} -> s{i:02}"));
+            gates.push(format!("y{i:02} AND x{i:02} -> h{i:02}"));
+            gates.push(format!("{} XOR s{i:02} -> z{i:02}", carry(i - 1)));
+            gates.push(format!("s{i:02} AND {} -> p{i:02}", carry(i - 1)));
+            gates.push(format!("h{i:02} OR p{i:02} -> {}", carry(i)));
+        }
+
+        let gates = gates
+            .iter()
+            .map(|line| Gate::new(line).unwrap())
+            .collect_vec();
+        let mut all_gates = gates_by_output(&gates);
+        swap_outputs(&mut all_gates, swaps);
+
+        let gates = all_gates
+            .into_values()
+            .sorted_by(|a, b| a.out.cmp(&b.out))
+            .map(|gate| {
+                let operator = match gate.operator {
+                    Operator::Xor => "XOR",
+                    Operator::And => "AND",
+                    Operator::Or => "OR",
+                };
+                format!("{} {} {} -> {}", gate.in_a, operator, gate.in_b, gate.out)
+            })
+            .join("\n");
+
+        format!("{given}\n\n{gates}")
+    }
+
+    #[test]
+    fn test_simulate_adder() {
+        let input = adder_input(45, &[]);
+        let x = 0b1011_0110_1110_0101_1111;
+        let y = 2u64.pow(44) + 0b1101_0011_0101;
+        let all_gates = gates_by_output(&parse_gates(&input).unwrap());
+        assert_eq!(simulate_part_2(&all_gates, x, y), Some(x + y));
+    }
+
+    #[test]
+    fn test_part_two_swapped_adder() {
+        let swaps = [
+            ("z06", "c06"),
+            ("s11", "h11"),
+            ("z16", "p16"),
+            ("z30", "h30"),
+        ];
+        let input = adder_input(45, &swaps);
+        let mut all_gates = gates_by_output(&parse_gates(&input).unwrap());
+
+        let x = 2u64.pow(45) - 1;
+        assert_ne!(simulate_part_2(&all_gates, x, 1), Some(x + 1));
+        assert!(adds_correctly(&mut all_gates, &swaps));
+        // The swaps are undone afterwards
+        assert_ne!(simulate_part_2(&all_gates, x, 1), Some(x + 1));
+        swap_outputs(&mut all_gates, &swaps);
+        assert_eq!(simulate_part_2(&all_gates, x, 1), Some(x + 1));
+
+        let result = part_two(&input);
+        assert_eq!(result, Some("c06,h11,h30,p16,s11,z06,z16,z30".to_string()));
+    }
+
+    #[test]
+    fn test_part_two_intermediate_wire_names() {
+        // Only x and y followed by digits are inputs, and z followed by digits outputs
+        let swaps = [
+            ("z06", "c06"),
+            ("s11", "h11"),
+            ("z16", "p16"),
+            ("z30", "h30"),
+        ];
+        let input = adder_input(45, &swaps)
+            .replace("h30", "xkb")
+            .replace("p16", "ykz")
+            .replace("c20", "zkb");
+
+        let result = part_two(&input);
+        assert_eq!(result, Some("c06,h11,s11,xkb,ykz,z06,z16,z30".to_string()));
+    }
 }
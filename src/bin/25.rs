@@ -1,85 +1,156 @@
-use itertools::Itertools;
-
 advent_of_code::solution!(25);
 
+const FILLED: char = '#';
+const EMPTY: char = '.';
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SchematicError {
+    Empty,
+    RaggedRow(usize),
+    UnexpectedChar {
+        row: usize,
+        col: usize,
+        c: char,
+    },
+    // Locks have a filled top row and keys a filled bottom row
+    NoBase,
+    // The filled cells of a column don't reach from the base to the pin height
+    BrokenColumn(usize),
+    // Too many cells for the pin bitmask
+    TooLarge {
+        rows: usize,
+        cols: usize,
+    },
+    DimensionMismatch {
+        expected: (usize, usize),
+        found: (usize, usize),
+    },
+}
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 struct KeyComponent {
+    // Pin heights, not counting the base row
     columns: Vec<u32>,
     is_key: bool, // Keyhole if false
+    // The filled cells, one bit per cell in row-major order. A key fits a lock exactly when no
+    // cell is filled in both.
+    pins: u64,
+    rows: usize,
 }
 
-fn parse_columns(s: &str) -> Vec<u32> {
-    let chars: Vec<Vec<char>> = s.lines().map(|line| line.chars().collect()).collect();
-    dbg!(chars.clone());
-    dbg!(chars.len());
-    dbg!(chars.iter().nth(0).unwrap().len());
-    let mut out: Vec<u32> = vec![];
-    for c in 0..5 {
-        for r in 0..6 {
-            if chars[r + 1][c] == '.' {
-                out.push(r.try_into().expect("Should convert"));
-                break;
-            }
+impl KeyComponent {
+    fn parse(s: &str) -> Result<KeyComponent, SchematicError> {
+        let grid: Vec<Vec<char>> = s.lines().map(|line| line.chars().collect()).collect();
+        let rows = grid.len();
+        let cols = grid.first().map_or(0, |row| row.len());
+        if rows == 0 || cols == 0 {
+            return Err(SchematicError::Empty);
+        }
+        if rows * cols > u64::BITS as usize {
+            return Err(SchematicError::TooLarge { rows, cols });
         }
-    }
-    out
-}
 
-fn fits(key: &Vec<u32>, hole: &Vec<u32>) -> bool {
-    for (key_col, hole_col) in key.iter().zip(hole.iter()) {
-        if (key_col + hole_col) > 5 {
-            return false;
+        let mut pins = 0;
+        for (row, line) in grid.iter().enumerate() {
+            if line.len() != cols {
+                return Err(SchematicError::RaggedRow(row));
+            }
+            for (col, c) in line.iter().enumerate() {
+                match *c {
+                    FILLED => pins |= 1 << (row * cols + col),
+                    EMPTY => {}
+                    c => return Err(SchematicError::UnexpectedChar { row, col, c }),
+                }
+            }
         }
-    }
-    true
-}
 
-impl KeyComponent {
-    fn new(s: &str) -> Option<KeyComponent> {
-        let is_key = s.lines().nth(0)?.chars().all(|c| c == '.');
-
-        let columns = if is_key {
-            let reversed = s.lines().rev().collect_vec().join("\n");
-            parse_columns(&reversed)
-        } else {
-            parse_columns(s)
+        let is_filled = |row: usize| grid[row].iter().all(|c| *c == FILLED);
+        let is_key = match (is_filled(0), is_filled(rows - 1)) {
+            (true, false) => false,
+            (false, true) => true,
+            _ => return Err(SchematicError::NoBase),
         };
 
-        Some(KeyComponent { columns, is_key })
+        // Rows counted from the base towards the other end
+        let from_base = |i: usize| if is_key { rows - 1 - i } else { i };
+        let columns = (0..cols)
+            .map(|col| {
+                let filled = (0..rows)
+                    .take_while(|i| grid[from_base(*i)][col] == FILLED)
+                    .count();
+                let rest_empty = (filled..rows).all(|i| grid[from_base(i)][col] == EMPTY);
+                if rest_empty {
+                    Ok(filled as u32 - 1)
+                } else {
+                    Err(SchematicError::BrokenColumn(col))
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(KeyComponent {
+            columns,
+            is_key,
+            pins,
+            rows,
+        })
+    }
+
+    fn cols(&self) -> usize {
+        self.columns.len()
+    }
+
+    // A key and a lock of the same size fit if their pins don't overlap in any column
+    fn fits(&self, other: &KeyComponent) -> bool {
+        self.is_key != other.is_key && self.pins & other.pins == 0
     }
 }
 
-pub fn part_one(input: &str) -> Option<u32> {
-    let key_components = input
-        .split("\n\n")
-        .map(|line| KeyComponent::new(line))
-        .collect::<Option<Vec<KeyComponent>>>()
-        .expect("Should parse!");
-
-    let keys = key_components
-        .clone()
-        .into_iter()
-        .filter(|kc| kc.is_key)
-        .collect_vec();
-    let holes = key_components
-        .clone()
-        .into_iter()
-        .filter(|kc| !kc.is_key)
-        .collect_vec();
-
-    let mut num_fits = 0;
-    for key in &keys {
-        for hole in &holes {
-            if fits(&key.columns, &hole.columns) {
-                num_fits += 1;
+struct Schematics {
+    locks: Vec<KeyComponent>,
+    keys: Vec<KeyComponent>,
+}
+
+impl Schematics {
+    // All schematics must have the size of the first one. Errors come with the index of the
+    // malformed block.
+    fn parse(input: &str) -> Result<Schematics, (usize, SchematicError)> {
+        let mut locks = vec![];
+        let mut keys = vec![];
+        let mut size = None;
+
+        for (block, s) in input.trim().split("\n\n").enumerate() {
+            let component = KeyComponent::parse(s).map_err(|error| (block, error))?;
+
+            let found = (component.rows, component.cols());
+            let expected = *size.get_or_insert(found);
+            if found != expected {
+                return Err((block, SchematicError::DimensionMismatch { expected, found }));
+            }
+
+            if component.is_key {
+                keys.push(component);
+            } else {
+                locks.push(component);
             }
         }
+
+        Ok(Schematics { locks, keys })
+    }
+
+    fn fitting_pairs(&self) -> usize {
+        self.locks
+            .iter()
+            .map(|lock| self.keys.iter().filter(|key| key.fits(lock)).count())
+            .sum()
     }
+}
 
-    Some(num_fits)
+pub fn part_one(input: &str) -> Option<u32> {
+    let schematics = Schematics::parse(input).ok()?;
+    schematics.fitting_pairs().try_into().ok()
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
+pub fn part_two(_input: &str) -> Option<u32> {
     None
 }
 
@@ -89,37 +160,84 @@ mod tests {
 
     #[test]
     fn test_parse_lock() {
-        let result = KeyComponent::new(&advent_of_code::template::read_file_part(
+        let result = KeyComponent::parse(&advent_of_code::template::read_file_part(
             "examples", DAY, 1,
-        ));
-        assert_eq!(
-            result,
-            Some(KeyComponent {
-                columns: vec![0, 5, 3, 4, 3],
-                is_key: false
-            })
-        );
+        ))
+        .unwrap();
+        assert_eq!(result.columns, vec![0, 5, 3, 4, 3]);
+        assert!(!result.is_key);
+        assert_eq!(result.rows, 7);
     }
 
     #[test]
     fn test_parse_key() {
-        let result = KeyComponent::new(&advent_of_code::template::read_file_part(
+        let result = KeyComponent::parse(&advent_of_code::template::read_file_part(
             "examples", DAY, 2,
-        ));
+        ))
+        .unwrap();
+        assert_eq!(result.columns, vec![5, 0, 2, 1, 3]);
+        assert!(result.is_key);
+    }
+
+    #[test]
+    fn test_fits() {
+        let lock = KeyComponent::parse("###\n#.#\n...\n...").unwrap();
+        let key = KeyComponent::parse("...\n.##\n.##\n###").unwrap();
+        let other_key = KeyComponent::parse("...\n...\n#..\n###").unwrap();
+        assert_eq!(lock.columns, vec![1, 0, 1]);
+        assert_eq!(key.columns, vec![0, 2, 2]);
+        assert!(!lock.fits(&key));
+        assert!(lock.fits(&other_key));
+        assert!(other_key.fits(&lock));
+        assert!(!key.fits(&other_key));
+    }
+
+    #[test]
+    fn test_malformed_schematics() {
+        assert_eq!(KeyComponent::parse(""), Err(SchematicError::Empty));
         assert_eq!(
-            result,
-            Some(KeyComponent {
-                columns: vec![5, 0, 2, 1, 3],
-                is_key: true
+            KeyComponent::parse("###\n##\n..."),
+            Err(SchematicError::RaggedRow(1))
+        );
+        assert_eq!(
+            KeyComponent::parse("###\n#x.\n..."),
+            Err(SchematicError::UnexpectedChar {
+                row: 1,
+                col: 1,
+                c: 'x'
             })
         );
+        assert_eq!(
+            KeyComponent::parse("#.#\n...\n#.#"),
+            Err(SchematicError::NoBase)
+        );
+        assert_eq!(
+            KeyComponent::parse("###\n..#\n#..\n..."),
+            Err(SchematicError::BrokenColumn(0))
+        );
+        assert_eq!(
+            KeyComponent::parse(&"#########\n".repeat(8)),
+            Err(SchematicError::TooLarge { rows: 8, cols: 9 })
+        );
+
+        let result = Schematics::parse("###\n...\n\n..\n##\n\n...\n###");
+        assert_eq!(
+            result.err(),
+            Some((
+                1,
+                SchematicError::DimensionMismatch {
+                    expected: (2, 3),
+                    found: (2, 2)
+                }
+            ))
+        );
     }
 
-    // #[test]
-    // fn test_part_one() {
-    //     let result = part_one(&advent_of_code::template::read_file("examples", DAY));
-    //     assert_eq!(result, Some(3));
-    // }
+    #[test]
+    fn test_part_one() {
+        let result = part_one(&advent_of_code::template::read_file("examples", DAY));
+        assert_eq!(result, Some(3));
+    }
 
     #[test]
     fn test_part_two() {
@@ -0,0 +1,226 @@
+use std::{collections::HashMap, hash::Hash};
+
+/// A cycle in a sequence of states: the state at step `start` reappears every `length` steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+impl Cycle {
+    /// Maps step `n` to the earliest step with the same state.
+    pub fn equivalent_step(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.length
+        }
+    }
+}
+
+// All detectors take a step function returning `None` when the sequence ends, in which case there
+// is no cycle to find.
+
+/// Floyd's tortoise and hare. Needs constant memory, but steps through the sequence about three times.
+pub fn floyd<T: Clone + PartialEq>(
+    initial: T,
+    mut step: impl FnMut(&T) -> Option<T>,
+) -> Option<Cycle> {
+    let mut tortoise = step(&initial)?;
+    let mut hare = step(&tortoise)?;
+    while tortoise != hare {
+        tortoise = step(&tortoise)?;
+        let next = step(&hare)?;
+        hare = step(&next)?;
+    }
+
+    // The distance from the start to the cycle equals the distance from the meeting point to it
+    let mut start = 0;
+    tortoise = initial;
+    while tortoise != hare {
+        tortoise = step(&tortoise)?;
+        hare = step(&hare)?;
+        start += 1;
+    }
+
+    let mut length = 1;
+    hare = step(&tortoise)?;
+    while tortoise != hare {
+        hare = step(&hare)?;
+        length += 1;
+    }
+
+    Some(Cycle { start, length })
+}
+
+/// Brent's algorithm. Needs constant memory, and usually fewer steps than [`floyd`].
+pub fn brent<T: Clone + PartialEq>(
+    initial: T,
+    mut step: impl FnMut(&T) -> Option<T>,
+) -> Option<Cycle> {
+    // Find the cycle length by searching in windows of increasing powers of two
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial)?;
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = step(&hare)?;
+        length += 1;
+    }
+
+    // With the hare `length` steps ahead, they meet at the start of the cycle
+    tortoise = initial.clone();
+    hare = initial;
+    for _ in 0..length {
+        hare = step(&hare)?;
+    }
+
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise)?;
+        hare = step(&hare)?;
+        start += 1;
+    }
+
+    Some(Cycle { start, length })
+}
+
+/// Steps through the sequence, remembering every state, until a state repeats or the sequence ends.
+/// Returns all states seen, in order, and the cycle if one was found.
+pub fn history<T: Clone + Hash + Eq>(
+    initial: T,
+    mut step: impl FnMut(&T) -> Option<T>,
+) -> (Vec<T>, Option<Cycle>) {
+    let mut seen: HashMap<T, usize> = HashMap::new();
+    let mut states: Vec<T> = vec![];
+    let mut current = Some(initial);
+
+    while let Some(state) = current {
+        if let Some(&start) = seen.get(&state) {
+            let length = states.len() - start;
+            return (states, Some(Cycle { start, length }));
+        }
+
+        current = step(&state);
+        seen.insert(state.clone(), states.len());
+        states.push(state);
+    }
+
+    (states, None)
+}
+
+/// Hash-based cycle detection. Uses memory for every state, but steps through the sequence only once.
+pub fn find_cycle<T: Clone + Hash + Eq>(
+    initial: T,
+    step: impl FnMut(&T) -> Option<T>,
+) -> Option<Cycle> {
+    history(initial, step).1
+}
+
+/// Fast-forwards to the state after `n` steps, skipping ahead once a cycle is detected.
+/// Returns `None` if the sequence ends before step `n`.
+pub fn state_at<T: Clone + Hash + Eq>(
+    initial: T,
+    mut step: impl FnMut(&T) -> Option<T>,
+    n: usize,
+) -> Option<T> {
+    let mut steps_left = n;
+    let (states, cycle) = history(initial, |state| {
+        // No need to look for a cycle once step n has been reached
+        if steps_left == 0 {
+            return None;
+        }
+        steps_left -= 1;
+        step(state)
+    });
+
+    match cycle {
+        Some(cycle) => states.get(cycle.equivalent_step(n)).cloned(),
+        None => states.get(n).cloned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 3, 10, 101, 2, 5, 26, 167, 95, 101, ... with a cycle of length 6 starting at step 2
+    fn rho(x: &u64) -> Option<u64> {
+        Some((x * x + 1) % 255)
+    }
+
+    #[test]
+    fn test_floyd() {
+        assert_eq!(
+            floyd(3, rho),
+            Some(Cycle {
+                start: 2,
+                length: 6
+            })
+        );
+    }
+
+    #[test]
+    fn test_brent() {
+        assert_eq!(
+            brent(3, rho),
+            Some(Cycle {
+                start: 2,
+                length: 6
+            })
+        );
+    }
+
+    #[test]
+    fn test_find_cycle() {
+        assert_eq!(
+            find_cycle(3, rho),
+            Some(Cycle {
+                start: 2,
+                length: 6
+            })
+        );
+    }
+
+    #[test]
+    fn test_cycle_at_start() {
+        let step = |x: &u32| Some((x + 1) % 7);
+        let expected = Some(Cycle {
+            start: 0,
+            length: 7,
+        });
+        assert_eq!(floyd(0, step), expected);
+        assert_eq!(brent(0, step), expected);
+        assert_eq!(find_cycle(0, step), expected);
+    }
+
+    #[test]
+    fn test_no_cycle() {
+        let step = |x: &u32| if *x < 100 { Some(x + 1) } else { None };
+        assert_eq!(floyd(0, step), None);
+        assert_eq!(brent(0, step), None);
+        assert_eq!(find_cycle(0, step), None);
+    }
+
+    #[test]
+    fn test_state_at() {
+        let mut brute_force = 3;
+        for n in 0..50 {
+            assert_eq!(state_at(3, rho, n), Some(brute_force));
+            brute_force = rho(&brute_force).unwrap();
+        }
+        assert_eq!(state_at(3, rho, 1_000_000_000_000), Some(5));
+    }
+
+    #[test]
+    fn test_state_at_after_end() {
+        let step = |x: &u32| if *x < 10 { Some(x + 1) } else { None };
+        assert_eq!(state_at(0, step, 10), Some(10));
+        assert_eq!(state_at(0, step, 11), None);
+    }
+}
@@ -0,0 +1,402 @@
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+
+/// Index of a node in a [`Graph`], handed out in the order the nodes were first seen.
+pub type NodeId = usize;
+
+/// A set of small integers, one bit each. Grows as needed when inserting.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    pub fn new() -> BitSet {
+        BitSet::default()
+    }
+
+    /// A set containing every value below `len`.
+    pub fn full(len: usize) -> BitSet {
+        let mut words = vec![u64::MAX; len / 64];
+        if !len.is_multiple_of(64) {
+            words.push((1 << (len % 64)) - 1);
+        }
+        BitSet { words }
+    }
+
+    pub fn insert(&mut self, value: usize) {
+        let word = value / 64;
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+        self.words[word] |= 1 << (value % 64);
+    }
+
+    pub fn remove(&mut self, value: usize) {
+        if let Some(word) = self.words.get_mut(value / 64) {
+            *word &= !(1 << (value % 64));
+        }
+    }
+
+    pub fn contains(&self, value: usize) -> bool {
+        self.words
+            .get(value / 64)
+            .is_some_and(|word| word & (1 << (value % 64)) != 0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|word| *word == 0)
+    }
+
+    pub fn intersection(&self, other: &BitSet) -> BitSet {
+        let words = self
+            .words
+            .iter()
+            .zip(&other.words)
+            .map(|(a, b)| a & b)
+            .collect();
+        BitSet { words }
+    }
+
+    pub fn difference(&self, other: &BitSet) -> BitSet {
+        let words = self
+            .words
+            .iter()
+            .enumerate()
+            .map(|(i, a)| a & !other.words.get(i).unwrap_or(&0))
+            .collect();
+        BitSet { words }
+    }
+
+    /// Adds every value of the other set to this one.
+    pub fn union_with(&mut self, other: &BitSet) {
+        if other.words.len() > self.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+        for (word, other_word) in self.words.iter_mut().zip(&other.words) {
+            *word |= other_word;
+        }
+    }
+
+    /// Size of the intersection, without building it.
+    pub fn intersection_len(&self, other: &BitSet) -> usize {
+        self.words
+            .iter()
+            .zip(&other.words)
+            .map(|(a, b)| (a & b).count_ones() as usize)
+            .sum()
+    }
+
+    /// The values in the set, in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, word)| {
+            let mut remaining = *word;
+            std::iter::from_fn(move || {
+                if remaining == 0 {
+                    return None;
+                }
+                let bit = remaining.trailing_zeros() as usize;
+                remaining &= remaining - 1;
+                Some(i * 64 + bit)
+            })
+        })
+    }
+}
+
+impl FromIterator<usize> for BitSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> BitSet {
+        let mut set = BitSet::new();
+        for value in iter {
+            set.insert(value);
+        }
+        set
+    }
+}
+
+/// An undirected graph without parallel edges. Nodes are named by any hashable value, which is
+/// interned to a [`NodeId`] so the algorithms can work on bitsets instead of the names.
+#[derive(Debug, Clone)]
+pub struct Graph<N> {
+    names: Vec<N>,
+    ids: HashMap<N, NodeId>,
+    adjacency: Vec<BitSet>,
+}
+
+impl<N: Clone + Hash + Eq> Default for Graph<N> {
+    fn default() -> Self {
+        Graph {
+            names: vec![],
+            ids: HashMap::new(),
+            adjacency: vec![],
+        }
+    }
+}
+
+impl<N: Clone + Hash + Eq> Graph<N> {
+    pub fn new() -> Graph<N> {
+        Graph::default()
+    }
+
+    /// Builds the graph from the edges, adding the nodes as they appear.
+    pub fn from_edges(edges: impl IntoIterator<Item = (N, N)>) -> Graph<N> {
+        let mut graph = Graph::new();
+        for (a, b) in edges {
+            graph.add_edge(a, b);
+        }
+        graph
+    }
+
+    /// The id of the node, adding it if it's new.
+    pub fn intern(&mut self, name: N) -> NodeId {
+        if let Some(id) = self.ids.get(&name) {
+            return *id;
+        }
+        let id = self.names.len();
+        self.names.push(name.clone());
+        self.ids.insert(name, id);
+        self.adjacency.push(BitSet::new());
+        id
+    }
+
+    pub fn id<Q>(&self, name: &Q) -> Option<NodeId>
+    where
+        N: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: NodeId) -> &N {
+        &self.names[id]
+    }
+
+    pub fn add_edge(&mut self, a: N, b: N) {
+        let a = self.intern(a);
+        let b = self.intern(b);
+        self.add_edge_ids(a, b);
+    }
+
+    /// Connects two nodes. Self-loops are ignored: a node is never its own neighbour, which the
+    /// clique searches rely on.
+    pub fn add_edge_ids(&mut self, a: NodeId, b: NodeId) {
+        if a == b {
+            return;
+        }
+        self.adjacency[a].insert(b);
+        self.adjacency[b].insert(a);
+    }
+
+    /// Number of nodes.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = NodeId> {
+        0..self.len()
+    }
+
+    pub fn neighbors(&self, id: NodeId) -> &BitSet {
+        &self.adjacency[id]
+    }
+
+    pub fn has_edge(&self, a: NodeId, b: NodeId) -> bool {
+        self.adjacency[a].contains(b)
+    }
+
+    pub fn degree(&self, id: NodeId) -> usize {
+        self.adjacency[id].len()
+    }
+
+    /// Every set of three nodes that are all connected, each listed once with increasing ids.
+    pub fn triangles(&self) -> Vec<[NodeId; 3]> {
+        let mut triangles = vec![];
+        for a in self.nodes() {
+            for b in self.adjacency[a].iter().filter(|b| *b > a) {
+                let common = self.adjacency[a].intersection(&self.adjacency[b]);
+                triangles.extend(common.iter().filter(|c| *c > b).map(|c| [a, b, c]));
+            }
+        }
+        triangles
+    }
+
+    /// Every maximal clique, found by Bron–Kerbosch with pivoting. Each clique is sorted by id.
+    pub fn maximal_cliques(&self) -> Vec<Vec<NodeId>> {
+        let mut cliques = vec![];
+        self.bron_kerbosch(
+            &mut vec![],
+            BitSet::full(self.len()),
+            BitSet::new(),
+            &mut cliques,
+        );
+        cliques
+    }
+
+    // Extends `required` with nodes from `possible` in every way that gives a maximal clique. Nodes
+    // in `excluded` have already been tried, so cliques containing them have already been reported.
+    fn bron_kerbosch(
+        &self,
+        required: &mut Vec<NodeId>,
+        mut possible: BitSet,
+        mut excluded: BitSet,
+        cliques: &mut Vec<Vec<NodeId>>,
+    ) {
+        if possible.is_empty() {
+            if excluded.is_empty() {
+                let mut clique = required.clone();
+                clique.sort();
+                cliques.push(clique);
+            }
+            return;
+        }
+
+        // Every maximal clique contains the pivot or one of its non-neighbors, so the pivot's
+        // neighbors don't have to be tried. Picking the pivot with most neighbors skips the most.
+        let pivot = possible
+            .iter()
+            .chain(excluded.iter())
+            .max_by_key(|node| possible.intersection_len(&self.adjacency[*node]))
+            .expect("Should have a pivot when possible is non-empty");
+
+        for node in possible
+            .difference(&self.adjacency[pivot])
+            .iter()
+            .collect::<Vec<_>>()
+        {
+            let neighbors = &self.adjacency[node];
+            required.push(node);
+            self.bron_kerbosch(
+                required,
+                possible.intersection(neighbors),
+                excluded.intersection(neighbors),
+                cliques,
+            );
+            required.pop();
+
+            possible.remove(node);
+            excluded.insert(node);
+        }
+    }
+
+    /// A largest clique, sorted by id. Empty for an empty graph.
+    pub fn maximum_clique(&self) -> Vec<NodeId> {
+        self.maximal_cliques()
+            .into_iter()
+            .max_by_key(|clique| clique.len())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two squares sharing the edge c-d, with both diagonals in the first one
+    fn example() -> Graph<&'static str> {
+        Graph::from_edges([
+            ("a", "b"),
+            ("a", "c"),
+            ("a", "d"),
+            ("b", "c"),
+            ("b", "d"),
+            ("c", "d"),
+            ("c", "e"),
+            ("d", "f"),
+            ("e", "f"),
+        ])
+    }
+
+    #[test]
+    fn test_bitset() {
+        let mut set: BitSet = [1, 64, 130].into_iter().collect();
+        assert!(set.contains(64));
+        assert!(!set.contains(2));
+        assert_eq!(set.len(), 3);
+
+        set.remove(64);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![1, 130]);
+
+        let full = BitSet::full(70);
+        assert_eq!(full.len(), 70);
+        assert!(!full.contains(70));
+        assert_eq!(full.intersection(&set).iter().collect::<Vec<_>>(), vec![1]);
+        assert_eq!(full.difference(&set).len(), 69);
+        assert_eq!(full.intersection_len(&set), 1);
+
+        let mut union: BitSet = [2].into_iter().collect();
+        union.union_with(&set);
+        assert_eq!(union.iter().collect::<Vec<_>>(), vec![1, 2, 130]);
+    }
+
+    #[test]
+    fn test_interning() {
+        let mut graph = example();
+        assert_eq!(graph.len(), 6);
+        assert_eq!(graph.id("c"), Some(2));
+        assert_eq!(graph.name(2), &"c");
+        assert_eq!(graph.intern("c"), 2);
+        assert_eq!(graph.id("g"), None);
+
+        assert!(graph.has_edge(0, 3));
+        assert!(!graph.has_edge(0, 4));
+        assert_eq!(graph.degree(2), 4);
+    }
+
+    #[test]
+    fn test_triangles() {
+        let graph = example();
+        let names = graph
+            .triangles()
+            .into_iter()
+            .map(|triangle| triangle.map(|id| *graph.name(id)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                ["a", "b", "c"],
+                ["a", "b", "d"],
+                ["a", "c", "d"],
+                ["b", "c", "d"]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_maximal_cliques() {
+        let graph = example();
+        let mut cliques = graph.maximal_cliques();
+        cliques.sort();
+        assert_eq!(
+            cliques,
+            vec![vec![0, 1, 2, 3], vec![2, 4], vec![3, 5], vec![4, 5]]
+        );
+        assert_eq!(graph.maximum_clique(), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_empty_graph() {
+        let graph: Graph<u32> = Graph::new();
+        assert!(graph.is_empty());
+        assert!(graph.triangles().is_empty());
+        assert!(graph.maximum_clique().is_empty());
+    }
+
+    #[test]
+    fn test_self_loops_ignored() {
+        let graph = Graph::from_edges([("a", "a"), ("a", "b"), ("b", "c")]);
+        assert!(!graph.has_edge(0, 0));
+        assert_eq!(graph.degree(0), 1);
+        let mut cliques = graph.maximal_cliques();
+        cliques.sort();
+        assert_eq!(cliques, vec![vec![0, 1], vec![1, 2]]);
+    }
+}
@@ -1,3 +1,8 @@
 pub mod template;
 
 // Use this file to add helper functions and additional modules.
+pub mod cycle;
+pub mod graph;
+pub mod lists;
+pub mod ordering;
+pub mod regions;
@@ -0,0 +1,91 @@
+use std::{collections::HashMap, hash::Hash, str::FromStr};
+
+/// Parses lines of `n` whitespace-separated values into one vector per column. Blank lines are
+/// skipped, and any other line with a different number of values fails the parse.
+pub fn parse_columns<T: FromStr>(input: &str, n: usize) -> Option<Vec<Vec<T>>> {
+    let mut columns: Vec<Vec<T>> = (0..n).map(|_| vec![]).collect();
+
+    for line in input.lines().filter(|line| !line.trim().is_empty()) {
+        let mut values = line.split_whitespace();
+        for column in columns.iter_mut() {
+            column.push(values.next()?.parse().ok()?);
+        }
+        if values.next().is_some() {
+            return None;
+        }
+    }
+
+    Some(columns)
+}
+
+/// Number of times every value appears.
+pub fn frequencies<T: Hash + Eq>(values: impl IntoIterator<Item = T>) -> HashMap<T, usize> {
+    let mut counts = HashMap::new();
+    for value in values {
+        *counts.entry(value).or_default() += 1;
+    }
+    counts
+}
+
+/// Pairs up the smallest values of both lists, then the second smallest and so on, and sums the
+/// distances between the pairs. Extra values in the longer list are ignored.
+pub fn sorted_distance(left: &[i64], right: &[i64]) -> u64 {
+    let mut left = left.to_vec();
+    let mut right = right.to_vec();
+    left.sort_unstable();
+    right.sort_unstable();
+
+    left.iter().zip(&right).map(|(l, r)| l.abs_diff(*r)).sum()
+}
+
+/// Sums every value of the left list multiplied by the number of times it appears in the right list.
+pub fn similarity_score(left: &[i64], right: &[i64]) -> i64 {
+    let right_counts = frequencies(right.iter().copied());
+
+    left.iter()
+        .map(|value| value * right_counts.get(value).copied().unwrap_or(0) as i64)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_columns() {
+        let columns: Vec<Vec<i64>> = parse_columns("1   2 3\n4\t5  6\n\n7 8 9\n", 3).unwrap();
+        assert_eq!(columns, vec![vec![1, 4, 7], vec![2, 5, 8], vec![3, 6, 9]]);
+
+        assert_eq!(parse_columns::<i64>("1 2\n3", 2), None);
+        assert_eq!(parse_columns::<i64>("1 2 3", 2), None);
+        assert_eq!(parse_columns::<i64>("1 x", 2), None);
+        assert_eq!(parse_columns::<i64>("", 2), Some(vec![vec![], vec![]]));
+    }
+
+    #[test]
+    fn test_frequencies() {
+        let counts = frequencies([3, 4, 3]);
+
+        assert_eq!(Some(&2), counts.get(&3));
+        assert_eq!(Some(&1), counts.get(&4));
+        assert_eq!(None, counts.get(&1));
+    }
+
+    #[test]
+    fn test_sorted_distance() {
+        assert_eq!(
+            sorted_distance(&[3, 4, 2, 1, 3, 3], &[4, 3, 5, 3, 9, 3]),
+            11
+        );
+        assert_eq!(sorted_distance(&[-5, 10], &[5]), 10);
+    }
+
+    #[test]
+    fn test_similarity_score() {
+        assert_eq!(
+            similarity_score(&[3, 4, 2, 1, 3, 3], &[4, 3, 5, 3, 9, 3]),
+            31
+        );
+        assert_eq!(similarity_score(&[1, 2], &[]), 0);
+    }
+}
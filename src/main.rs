@@ -26,6 +26,7 @@ mod args {
             day: Day,
             release: bool,
             dhat: bool,
+            debug: bool,
             submit: Option<u8>,
         },
         All {
@@ -35,6 +36,7 @@ mod args {
             all: bool,
             day: Option<Day>,
             store: bool,
+            cold: bool,
         },
         #[cfg(feature = "today")]
         Today,
@@ -50,11 +52,13 @@ mod args {
             Some("time") => {
                 let all = args.contains("--all");
                 let store = args.contains("--store");
+                let cold = args.contains("--cold");
 
                 AppArguments::Time {
                     all,
                     day: args.opt_free_from_str()?,
                     store,
+                    cold,
                 }
             }
             Some("download") => AppArguments::Download {
@@ -73,6 +77,7 @@ mod args {
                 release: args.contains("--release"),
                 submit: args.opt_value_from_str("--submit")?,
                 dhat: args.contains("--dhat"),
+                debug: args.contains("--debug"),
             },
             #[cfg(feature = "today")]
             Some("today") => AppArguments::Today,
@@ -103,7 +108,12 @@ fn main() {
         }
         Ok(args) => match args {
             AppArguments::All { release } => all::handle(release),
-            AppArguments::Time { day, all, store } => time::handle(day, all, store),
+            AppArguments::Time {
+                day,
+                all,
+                store,
+                cold,
+            } => time::handle(day, all, store, cold),
             AppArguments::Download { day } => download::handle(day),
             AppArguments::Read { day } => read::handle(day),
             AppArguments::Scaffold {
@@ -120,8 +130,9 @@ fn main() {
                 day,
                 release,
                 dhat,
+                debug,
                 submit,
-            } => solve::handle(day, release, dhat, submit),
+            } => solve::handle(day, release, dhat, debug, submit),
             #[cfg(feature = "today")]
             AppArguments::Today => {
                 match Day::today() {
@@ -0,0 +1,218 @@
+use std::{
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+/// Why a set of items can't be put in a single order by the rules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderError<T> {
+    /// The rules require every item to come before the next one, and the last before the first.
+    Cycle(Vec<T>),
+    /// No rule, directly or through other items, decides which of these comes first.
+    Ambiguous(T, T),
+    /// The item appears more than once, so it can't have a single position.
+    Duplicate(T),
+}
+
+/// Rules saying that one item must come before another. The rules as a whole don't have to be
+/// consistent: they're only ever applied to a subset of the items at a time.
+#[derive(Debug, Clone)]
+pub struct Precedence<T> {
+    successors: HashMap<T, HashSet<T>>,
+}
+
+impl<T: Copy + Hash + Eq> Default for Precedence<T> {
+    fn default() -> Self {
+        Precedence {
+            successors: HashMap::new(),
+        }
+    }
+}
+
+impl<T: Copy + Hash + Eq> Precedence<T> {
+    /// Builds the rules from pairs of (before, after).
+    pub fn from_rules(rules: impl IntoIterator<Item = (T, T)>) -> Precedence<T> {
+        let mut precedence = Precedence::default();
+        for (before, after) in rules {
+            precedence.add_rule(before, after);
+        }
+        precedence
+    }
+
+    pub fn add_rule(&mut self, before: T, after: T) {
+        self.successors.entry(before).or_default().insert(after);
+    }
+
+    /// Whether there's a rule saying `before` comes before `after`.
+    pub fn must_precede(&self, before: T, after: T) -> bool {
+        self.successors
+            .get(&before)
+            .is_some_and(|successors| successors.contains(&after))
+    }
+
+    /// Whether no rule is broken by the items in this order.
+    pub fn is_ordered(&self, items: &[T]) -> bool {
+        items.iter().enumerate().all(|(i, later)| {
+            items[..i]
+                .iter()
+                .all(|earlier| !self.must_precede(*later, *earlier))
+        })
+    }
+
+    /// Orders the items by the rules between them, ignoring rules about other items. Items that
+    /// no rule decides between keep their relative order from the input.
+    pub fn topological_order(&self, items: &[T]) -> Result<Vec<T>, OrderError<T>> {
+        self.kahn(items, false)
+    }
+
+    /// Like [`Precedence::topological_order`], but fails unless the rules decide the position of
+    /// every item, which is what sorting with the rules as a comparator silently relies on.
+    pub fn total_order(&self, items: &[T]) -> Result<Vec<T>, OrderError<T>> {
+        self.kahn(items, true)
+    }
+
+    // Kahn's algorithm on the rules restricted to the items. The order is unique exactly when a
+    // single item is ready at every step.
+    fn kahn(&self, items: &[T], strict: bool) -> Result<Vec<T>, OrderError<T>> {
+        let mut included: HashSet<T> = HashSet::with_capacity(items.len());
+        for item in items {
+            if !included.insert(*item) {
+                return Err(OrderError::Duplicate(*item));
+            }
+        }
+        let successors = |item: T| {
+            self.successors
+                .get(&item)
+                .into_iter()
+                .flatten()
+                .copied()
+                .filter(|next| included.contains(next))
+        };
+
+        let mut predecessors: HashMap<T, usize> = items.iter().map(|item| (*item, 0)).collect();
+        for item in &included {
+            for next in successors(*item) {
+                *predecessors.entry(next).or_default() += 1;
+            }
+        }
+
+        let mut order = vec![];
+        let mut placed = HashSet::new();
+        while order.len() < included.len() {
+            let mut ready = items
+                .iter()
+                .filter(|item| !placed.contains(*item) && predecessors[*item] == 0);
+            let Some(item) = ready.next().copied() else {
+                let remaining = items
+                    .iter()
+                    .copied()
+                    .filter(|item| !placed.contains(item))
+                    .collect::<Vec<_>>();
+                return Err(OrderError::Cycle(self.find_cycle(&remaining)));
+            };
+            if let (true, Some(other)) = (strict, ready.next()) {
+                return Err(OrderError::Ambiguous(item, *other));
+            }
+
+            for next in successors(item) {
+                *predecessors
+                    .get_mut(&next)
+                    .expect("Successors are included") -= 1;
+            }
+            placed.insert(item);
+            order.push(item);
+        }
+        Ok(order)
+    }
+
+    // A cycle among the items, which all have a predecessor among themselves, starting from the
+    // earliest of its items
+    fn find_cycle(&self, remaining: &[T]) -> Vec<T> {
+        let predecessor = |item: T| {
+            remaining
+                .iter()
+                .copied()
+                .find(|other| self.must_precede(*other, item))
+                .expect("Every remaining item has a remaining predecessor")
+        };
+
+        // Walking backwards has to come back to an item already seen
+        let mut path = vec![remaining[0]];
+        loop {
+            let previous = predecessor(*path.last().unwrap());
+            if let Some(start) = path.iter().position(|item| *item == previous) {
+                let mut cycle = path.split_off(start);
+                cycle.reverse();
+                let first = (0..cycle.len())
+                    .min_by_key(|i| remaining.iter().position(|item| *item == cycle[*i]))
+                    .unwrap_or(0);
+                cycle.rotate_left(first);
+                return cycle;
+            }
+            path.push(previous);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_ordered() {
+        let precedence = Precedence::from_rules([(1, 2), (2, 3)]);
+        assert!(precedence.must_precede(1, 2));
+        assert!(!precedence.must_precede(1, 3));
+        assert!(precedence.is_ordered(&[1, 2, 3]));
+        assert!(precedence.is_ordered(&[3, 1]));
+        assert!(!precedence.is_ordered(&[2, 1, 3]));
+    }
+
+    #[test]
+    fn test_topological_order() {
+        let precedence = Precedence::from_rules([(1, 2), (2, 3), (1, 3)]);
+        assert_eq!(precedence.topological_order(&[3, 2, 1]), Ok(vec![1, 2, 3]));
+        assert_eq!(precedence.total_order(&[3, 2, 1]), Ok(vec![1, 2, 3]));
+
+        // Only the rule about 1 and 2 applies, so 4 stays first
+        assert_eq!(precedence.topological_order(&[4, 2, 1]), Ok(vec![4, 1, 2]));
+        assert_eq!(
+            precedence.total_order(&[4, 2, 1]),
+            Err(OrderError::Ambiguous(4, 1))
+        );
+    }
+
+    #[test]
+    fn test_order_ignores_rules_outside_items() {
+        // The rules as a whole form a cycle, but no subset of two does
+        let precedence = Precedence::from_rules([(1, 2), (2, 3), (3, 1)]);
+        assert_eq!(precedence.total_order(&[2, 1]), Ok(vec![1, 2]));
+        assert_eq!(precedence.total_order(&[1, 3]), Ok(vec![3, 1]));
+    }
+
+    #[test]
+    fn test_duplicates() {
+        let precedence = Precedence::from_rules([(1, 2), (2, 3)]);
+        assert_eq!(
+            precedence.topological_order(&[3, 1, 2, 1]),
+            Err(OrderError::Duplicate(1))
+        );
+        assert_eq!(
+            precedence.total_order(&[4, 4]),
+            Err(OrderError::Duplicate(4))
+        );
+    }
+
+    #[test]
+    fn test_cycle() {
+        let precedence = Precedence::from_rules([(1, 2), (2, 3), (3, 1), (0, 1), (3, 4)]);
+        assert_eq!(
+            precedence.topological_order(&[0, 1, 2, 3, 4]),
+            Err(OrderError::Cycle(vec![1, 2, 3]))
+        );
+        assert_eq!(
+            precedence.topological_order(&[3, 4, 2, 1]),
+            Err(OrderError::Cycle(vec![3, 1, 2]))
+        );
+    }
+}
@@ -0,0 +1,274 @@
+/// Coordinates in format (row, col)
+pub type Coordinate = (usize, usize);
+
+/// Which cells count as neighbors when growing a region.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connectivity {
+    /// Only cells sharing an edge.
+    Four,
+    /// Cells sharing an edge or a corner.
+    Eight,
+}
+
+const EDGE_OFFSETS: [(isize, isize); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+const CORNER_OFFSETS: [(isize, isize); 4] = [(-1, 1), (1, 1), (1, -1), (-1, -1)];
+
+impl Connectivity {
+    fn offsets(&self) -> impl Iterator<Item = (isize, isize)> {
+        let corners: &[(isize, isize)] = match self {
+            Connectivity::Four => &[],
+            Connectivity::Eight => &CORNER_OFFSETS,
+        };
+        EDGE_OFFSETS.into_iter().chain(corners.iter().copied())
+    }
+}
+
+/// Every cell of a grid labeled with the region it belongs to. Regions are numbered in the order
+/// their first cell appears in row-major order.
+#[derive(Debug, Clone)]
+pub struct Regions {
+    labels: Vec<usize>,
+    rows: usize,
+    cols: usize,
+    count: usize,
+}
+
+impl Regions {
+    /// Splits a grid into regions of connected cells with the same value. Rows shorter than the
+    /// first one are treated as if the missing cells were all different from their neighbors.
+    pub fn label<T: PartialEq>(grid: &[Vec<T>], connectivity: Connectivity) -> Regions {
+        let rows = grid.len();
+        let cols = grid.first().map_or(0, |row| row.len());
+        Regions::label_with(rows, cols, connectivity, |a, b| {
+            match (grid[a.0].get(a.1), grid[b.0].get(b.1)) {
+                (Some(a), Some(b)) => a == b,
+                _ => false,
+            }
+        })
+    }
+
+    /// Splits a `rows` x `cols` grid into regions, where neighbors are in the same region if
+    /// `same_region` says so.
+    pub fn label_with(
+        rows: usize,
+        cols: usize,
+        connectivity: Connectivity,
+        same_region: impl Fn(Coordinate, Coordinate) -> bool,
+    ) -> Regions {
+        let mut labels: Vec<Option<usize>> = vec![None; rows * cols];
+        let mut count = 0;
+
+        for start in 0..rows * cols {
+            if labels[start].is_some() {
+                continue;
+            }
+
+            // Flood fill from the first unlabeled cell
+            labels[start] = Some(count);
+            let mut stack = vec![(start / cols, start % cols)];
+            while let Some(coord) = stack.pop() {
+                for neighbor in neighbors(coord, rows, cols, connectivity) {
+                    let index = neighbor.0 * cols + neighbor.1;
+                    if labels[index].is_none() && same_region(coord, neighbor) {
+                        labels[index] = Some(count);
+                        stack.push(neighbor);
+                    }
+                }
+            }
+            count += 1;
+        }
+
+        Regions {
+            labels: labels.into_iter().flatten().collect(),
+            rows,
+            cols,
+            count,
+        }
+    }
+
+    /// Number of regions.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// The region of the cell, or `None` outside the grid.
+    pub fn region_of(&self, coord: Coordinate) -> Option<usize> {
+        if coord.0 < self.rows && coord.1 < self.cols {
+            Some(self.labels[coord.0 * self.cols + coord.1])
+        } else {
+            None
+        }
+    }
+
+    /// The cells of every region, in row-major order.
+    pub fn cells(&self) -> Vec<Vec<Coordinate>> {
+        let mut cells = vec![vec![]; self.count];
+        for (index, label) in self.labels.iter().enumerate() {
+            cells[*label].push((index / self.cols, index % self.cols));
+        }
+        cells
+    }
+
+    /// Number of cells in every region.
+    pub fn areas(&self) -> Vec<usize> {
+        let mut areas = vec![0; self.count];
+        for label in &self.labels {
+            areas[*label] += 1;
+        }
+        areas
+    }
+
+    /// Number of cell edges on the boundary of every region.
+    pub fn perimeters(&self) -> Vec<usize> {
+        let mut perimeters = vec![0; self.count];
+        for coord in self.coordinates() {
+            let label = self.labels[coord.0 * self.cols + coord.1];
+            perimeters[label] += EDGE_OFFSETS
+                .iter()
+                .filter(|offset| !self.in_region(coord, **offset, label))
+                .count();
+        }
+        perimeters
+    }
+
+    /// Number of straight sides on the boundary of every region. A polygon has as many sides as
+    /// corners, so this counts the corners of every cell that are corners of the region.
+    ///
+    /// Sides facing different ways are counted separately, even where they continue each other
+    /// in a straight line. That happens where a region touches itself at a corner, which with
+    /// [`Connectivity::Eight`] is also where its cells may meet.
+    pub fn sides(&self) -> Vec<usize> {
+        let mut sides = vec![0; self.count];
+        for coord in self.coordinates() {
+            let label = self.labels[coord.0 * self.cols + coord.1];
+            for diagonal in CORNER_OFFSETS {
+                let vertical = self.in_region(coord, (diagonal.0, 0), label);
+                let horizontal = self.in_region(coord, (0, diagonal.1), label);
+                let across = self.in_region(coord, diagonal, label);
+
+                let outer_corner = !vertical && !horizontal;
+                let inner_corner = vertical && horizontal && !across;
+                if outer_corner || inner_corner {
+                    sides[label] += 1;
+                }
+            }
+        }
+        sides
+    }
+
+    fn coordinates(&self) -> impl Iterator<Item = Coordinate> + '_ {
+        (0..self.rows).flat_map(move |row| (0..self.cols).map(move |col| (row, col)))
+    }
+
+    // Whether the cell at the offset from the coordinate is in the region
+    fn in_region(&self, coord: Coordinate, offset: (isize, isize), label: usize) -> bool {
+        offset_coordinate(coord, offset)
+            .and_then(|other| self.region_of(other))
+            .is_some_and(|other_label| other_label == label)
+    }
+}
+
+fn offset_coordinate(coord: Coordinate, offset: (isize, isize)) -> Option<Coordinate> {
+    Some((
+        coord.0.checked_add_signed(offset.0)?,
+        coord.1.checked_add_signed(offset.1)?,
+    ))
+}
+
+/// The neighbors of the cell that are inside a `rows` x `cols` grid.
+pub fn neighbors(
+    coord: Coordinate,
+    rows: usize,
+    cols: usize,
+    connectivity: Connectivity,
+) -> impl Iterator<Item = Coordinate> {
+    connectivity
+        .offsets()
+        .filter_map(move |offset| offset_coordinate(coord, offset))
+        .filter(move |neighbor| neighbor.0 < rows && neighbor.1 < cols)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(s: &str) -> Vec<Vec<char>> {
+        s.lines().map(|line| line.chars().collect()).collect()
+    }
+
+    #[test]
+    fn test_label() {
+        let grid = grid("AAB\nBAB\nBBA");
+        let regions = Regions::label(&grid, Connectivity::Four);
+        assert_eq!(regions.len(), 4);
+        assert_eq!(regions.region_of((0, 0)), Some(0));
+        assert_eq!(regions.region_of((1, 1)), Some(0));
+        assert_eq!(regions.region_of((0, 2)), Some(1));
+        assert_eq!(regions.region_of((1, 0)), Some(2));
+        assert_eq!(regions.region_of((2, 2)), Some(3));
+        assert_eq!(regions.region_of((3, 0)), None);
+
+        // The diagonal B's and A's join up
+        let regions = Regions::label(&grid, Connectivity::Eight);
+        assert_eq!(regions.len(), 2);
+        assert_eq!(
+            regions.cells(),
+            vec![
+                vec![(0, 0), (0, 1), (1, 1), (2, 2)],
+                vec![(0, 2), (1, 0), (1, 2), (2, 0), (2, 1)]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_measurements() {
+        // An E shape around two separate holes
+        let grid = grid("EEEEE\nEXXXX\nEEEEE\nEXXXX\nEEEEE");
+        let regions = Regions::label(&grid, Connectivity::Four);
+        assert_eq!(regions.areas(), vec![17, 4, 4]);
+        assert_eq!(regions.perimeters(), vec![36, 10, 10]);
+        assert_eq!(regions.sides(), vec![12, 4, 4]);
+    }
+
+    #[test]
+    fn test_sides_with_hole() {
+        let grid = grid("AAAA\nABBA\nAAAA");
+        let regions = Regions::label(&grid, Connectivity::Four);
+        assert_eq!(regions.sides(), vec![8, 4]);
+        assert_eq!(regions.perimeters(), vec![20, 6]);
+    }
+
+    #[test]
+    fn test_label_with() {
+        // Even and odd numbers on a 3x3 grid
+        let value = |coord: Coordinate| coord.0 * 3 + coord.1;
+        let regions = Regions::label_with(3, 3, Connectivity::Eight, |a, b| {
+            value(a) % 2 == value(b) % 2
+        });
+        assert_eq!(regions.areas(), vec![5, 4]);
+    }
+
+    #[test]
+    fn test_sides_touching_at_corners() {
+        // Two L shapes and three single cells, each connected only through corners
+        let grid = grid("AA.\nA.A\n.AA");
+        let regions = Regions::label(&grid, Connectivity::Eight);
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions.areas(), vec![6, 3]);
+        assert_eq!(regions.sides(), vec![12, 12]);
+
+        let regions = Regions::label(&grid, Connectivity::Four);
+        assert_eq!(regions.sides(), vec![6, 4, 4, 6, 4]);
+    }
+
+    #[test]
+    fn test_empty_grid() {
+        let regions = Regions::label::<char>(&[], Connectivity::Four);
+        assert!(regions.is_empty());
+        assert!(regions.sides().is_empty());
+    }
+}
@@ -39,6 +39,8 @@ pub fn read_file_part(folder: &str, day: Day, part: u8) -> String {
 /// Creates the constant `DAY` and sets up the input and runner for each part.
 ///
 /// The optional, second parameter (1 or 2) allows you to only run a single part of the solution.
+/// Alternatively, `debug = <function>` registers a report that `cargo solve <day> --debug` prints
+/// after the results.
 #[macro_export]
 macro_rules! solution {
     ($day:expr) => {
@@ -50,8 +52,11 @@ macro_rules! solution {
     ($day:expr, 2) => {
         $crate::solution!(@impl $day, [part_two, 2]);
     };
+    ($day:expr, debug = $debug:expr) => {
+        $crate::solution!(@impl $day, [part_one, 1] [part_two, 2] debug = $debug);
+    };
 
-    (@impl $day:expr, $( [$func:expr, $part:expr] )*) => {
+    (@impl $day:expr, $( [$func:expr, $part:expr] )* $( debug = $debug:expr )?) => {
         /// The current day.
         const DAY: $crate::template::Day = $crate::day!($day);
 
@@ -63,6 +68,7 @@ macro_rules! solution {
             use $crate::template::runner::*;
             let input = $crate::template::read_file("inputs", DAY);
             $( run_part($func, &input, DAY, $part); )*
+            $( run_debug($debug, &input); )?
         }
     };
 }
@@ -4,10 +4,15 @@ use crate::template::{Day, ANSI_BOLD, ANSI_ITALIC, ANSI_RESET};
 
 use super::{
     all_days,
-    timings::{Timing, Timings},
+    timings::{ColdTiming, Timing, Timings},
 };
 
-pub fn run_multi(days_to_run: &HashSet<Day>, is_release: bool, is_timed: bool) -> Option<Timings> {
+pub fn run_multi(
+    days_to_run: &HashSet<Day>,
+    is_release: bool,
+    is_timed: bool,
+    is_cold: bool,
+) -> Option<Timings> {
     let mut timings: Vec<Timing> = Vec::with_capacity(days_to_run.len());
 
     let mut need_space = false;
@@ -24,7 +29,7 @@ pub fn run_multi(days_to_run: &HashSet<Day>, is_release: bool, is_timed: bool) -
             println!("{ANSI_BOLD}Day {day}{ANSI_RESET}");
             println!("------");
 
-            let output = child_commands::run_solution(day, is_timed, is_release).unwrap();
+            let output = child_commands::run_solution(day, is_timed, is_release, is_cold).unwrap();
 
             if output.is_empty() {
                 println!("Not solved.");
@@ -40,6 +45,12 @@ pub fn run_multi(days_to_run: &HashSet<Day>, is_release: bool, is_timed: bool) -
         println!(
             "\n{ANSI_BOLD}Total (Run):{ANSI_RESET} {ANSI_ITALIC}{total_millis:.2}ms{ANSI_RESET}"
         );
+        if is_cold {
+            let total_cold_millis = timings.total_cold_millis();
+            println!(
+                "{ANSI_BOLD}Total (Run, cold):{ANSI_RESET} {ANSI_ITALIC}{total_cold_millis:.2}ms{ANSI_RESET}"
+            );
+        }
         Some(timings)
     } else {
         None
@@ -77,7 +88,12 @@ pub mod child_commands {
     };
 
     /// Run the solution bin for a given day
-    pub fn run_solution(day: Day, is_timed: bool, is_release: bool) -> Result<Vec<String>, Error> {
+    pub fn run_solution(
+        day: Day,
+        is_timed: bool,
+        is_release: bool,
+        is_cold: bool,
+    ) -> Result<Vec<String>, Error> {
         // skip command invocation for days that have not been scaffolded yet.
         if !Path::new(&get_path_for_bin(day)).exists() {
             return Ok(vec![]);
@@ -94,6 +110,10 @@ pub mod child_commands {
             // mirror `--time` flag to child invocations.
             args.push("--");
             args.push("--time");
+
+            if is_cold {
+                args.push("--cold");
+            }
         }
 
         // spawn child command with piped stdout/stderr.
@@ -134,6 +154,7 @@ pub mod child_commands {
             part_1: None,
             part_2: None,
             total_nanos: 0_f64,
+            cold: None,
         };
 
         output
@@ -152,6 +173,19 @@ pub mod child_commands {
                 Some((part, timing_str, nanos))
             })
             .for_each(|(part, timing_str, nanos)| {
+                // cold timings are printed on their own line, e.g. `Part 1 (cold): (1.2ms @ 10 samples)`
+                if part.contains("(cold)") {
+                    let cold = timings.cold.get_or_insert_with(super::ColdTiming::default);
+                    if part.contains("Part 1") {
+                        cold.part_1 = Some(timing_str.into());
+                    } else if part.contains("Part 2") {
+                        cold.part_2 = Some(timing_str.into());
+                    }
+
+                    cold.total_nanos += nanos;
+                    return;
+                }
+
                 if part.contains("Part 1") {
                     timings.part_1 = Some(timing_str.into());
                 } else if part.contains("Part 2") {
@@ -174,7 +208,7 @@ pub mod child_commands {
             .split(" samples)")
             .next()?
             .split('(')
-            .last()?
+            .next_back()?
             .split('@')
             .next()?
             .trim();
@@ -239,6 +273,26 @@ pub mod child_commands {
             assert_eq!(res.part_2.unwrap(), "100ms");
         }
 
+        #[test]
+        fn parses_cold_times_separately() {
+            let res = parse_exec_time(
+                &[
+                    "Part 1: 0 (74.13ns @ 100000 samples)".into(),
+                    "Part 1 (cold): (2ms @ 10 samples)".into(),
+                    "Part 2: 10 (74.13ms @ 99999 samples)".into(),
+                    "Part 2 (cold): (100ms @ 10 samples)".into(),
+                    "".into(),
+                ],
+                day!(1),
+            );
+            assert_approx_eq!(res.total_nanos, 74130074.13_f64);
+            assert_eq!(res.part_1.unwrap(), "74.13ns");
+            let cold = res.cold.unwrap();
+            assert_approx_eq!(cold.total_nanos, 102000000_f64);
+            assert_eq!(cold.part_1.unwrap(), "2ms");
+            assert_eq!(cold.part_2.unwrap(), "100ms");
+        }
+
         #[test]
         fn parses_missing_parts() {
             let res = parse_exec_time(
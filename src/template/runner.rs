@@ -9,7 +9,23 @@ use std::{cmp, env, process};
 use crate::template::ANSI_BOLD;
 use crate::template::{aoc_cli, Day, ANSI_ITALIC, ANSI_RESET};
 
+/// Flag mirrored from `cargo time --cold`. Additionally benches every part in fresh processes.
+const COLD_FLAG: &str = "--cold";
+
+/// Flag passed to the solution binary itself when it runs as a cold sample for the given part.
+const COLD_SAMPLE_FLAG: &str = "--cold-sample";
+
+/// Flag mirrored from `cargo solve --debug`. Prints the day's debug report after the results.
+const DEBUG_FLAG: &str = "--debug";
+
 pub fn run_part<I: Clone, T: Display>(func: impl Fn(I) -> Option<T>, input: I, day: Day, part: u8) {
+    if let Some(sample_part) = cold_sample_part() {
+        if sample_part == part {
+            run_cold_sample(func, input);
+        }
+        return;
+    }
+
     let part_str = format!("Part {part}");
 
     let (result, duration, samples) =
@@ -17,11 +33,38 @@ pub fn run_part<I: Clone, T: Display>(func: impl Fn(I) -> Option<T>, input: I, d
 
     print_result(&result, &part_str, &format_duration(&duration, samples));
 
+    if is_cold() {
+        let cold_str = format!("{part_str} (cold):");
+        print!("{cold_str}");
+        let cold = bench_cold(part);
+        print!("\r");
+        match cold {
+            Some((cold_duration, cold_samples)) => println!(
+                "{cold_str}{}",
+                format_duration(&cold_duration, cold_samples)
+            ),
+            None => println!("{cold_str} ✖                  "),
+        }
+    }
+
     if let Some(result) = result {
         submit_result(result, day, part);
     }
 }
 
+/// Prints the report registered with `solution!(<day>, debug = <function>)`, if requested.
+pub fn run_debug<T: Display>(report: impl Fn(&str) -> Option<T>, input: &str) {
+    if cold_sample_part().is_some() || !env::args().any(|x| x == DEBUG_FLAG) {
+        return;
+    }
+
+    println!();
+    match report(input) {
+        Some(report) => println!("{report}"),
+        None => println!("Debug report: ✖"),
+    }
+}
+
 /// Run a solution part. The behavior differs depending on whether we are running a release or debug build:
 ///  1. in debug, the function is executed once.
 ///  2. in release, the function is benched (approx. 1 second of execution time or 10 samples, whatever take longer.)
@@ -78,6 +121,70 @@ fn bench<I: Clone, T>(func: impl Fn(I) -> T, input: I, base_time: &Duration) ->
     )
 }
 
+fn is_cold() -> bool {
+    let args: Vec<String> = env::args().collect();
+    args.iter().any(|x| x == "--time") && args.iter().any(|x| x == COLD_FLAG)
+}
+
+fn cold_sample_part() -> Option<u8> {
+    let args: Vec<String> = env::args().collect();
+    let index = args.iter().position(|x| x == COLD_SAMPLE_FLAG)?;
+    args.get(index + 1)?.parse().ok()
+}
+
+/// Runs the part once and prints how long it took in nanoseconds, for the parent process to read.
+fn run_cold_sample<I, T>(func: impl Fn(I) -> T, input: I) {
+    let timer = Instant::now();
+    black_box(func(black_box(input)));
+    println!("{}", timer.elapsed().as_nanos());
+}
+
+/// Benches a part with every sample in a fresh process running the same binary, so that nothing
+/// a solution keeps between calls, such as a global memoization cache, carries over to the next
+/// sample. Starting processes is slow, so this takes fewer samples than the warm bench.
+/// Returns `None` if any sample fails to run or doesn't report its duration.
+fn bench_cold(part: u8) -> Option<(Duration, u128)> {
+    let mut stdout = stdout();
+
+    print!(" > {ANSI_ITALIC}benching cold{ANSI_RESET}");
+    let _ = stdout.flush();
+
+    let exe = env::current_exe().ok()?;
+    let run_sample = || {
+        let output = process::Command::new(&exe)
+            .args([COLD_SAMPLE_FLAG, &part.to_string()])
+            .output()
+            .ok()?;
+
+        if !output.status.success() {
+            return None;
+        }
+        parse_cold_sample(&String::from_utf8_lossy(&output.stdout))
+    };
+
+    // The warm timings can't tell how long a cold run takes, so the first sample decides
+    let mut timers: Vec<Duration> = vec![run_sample()?];
+    let bench_iterations =
+        (Duration::from_secs(1).as_nanos() / cmp::max(timers[0].as_nanos(), 10)).clamp(10, 100);
+
+    for _ in 1..bench_iterations {
+        timers.push(run_sample()?);
+    }
+
+    Some((
+        #[allow(clippy::cast_possible_truncation)]
+        Duration::from_nanos(average_duration(&timers) as u64),
+        bench_iterations,
+    ))
+}
+
+/// Reads the duration printed by `run_cold_sample`. It is the last line of the output, because
+/// a solution may print to stdout itself.
+fn parse_cold_sample(output: &str) -> Option<Duration> {
+    let nanos = output.lines().rev().find(|line| !line.trim().is_empty())?;
+    Some(Duration::from_nanos(nanos.trim().parse().ok()?))
+}
+
 fn average_duration(numbers: &[Duration]) -> u128 {
     numbers
         .iter()
@@ -167,3 +274,31 @@ fn submit_result<T: Display>(
     println!("Submitting result via aoc-cli...");
     Some(aoc_cli::submit(day, part, &result.to_string()))
 }
+
+/* -------------------------------------------------------------------------- */
+
+#[cfg(feature = "test_lib")]
+mod tests {
+    #[test]
+    fn cold_sample_duration() {
+        assert_eq!(
+            super::parse_cold_sample("1234\n"),
+            Some(super::Duration::from_nanos(1234))
+        );
+    }
+
+    #[test]
+    fn cold_sample_after_solution_output() {
+        let output = "Found 3 paths\n...#\n1234\n\n";
+        assert_eq!(
+            super::parse_cold_sample(output),
+            Some(super::Duration::from_nanos(1234))
+        );
+    }
+
+    #[test]
+    fn cold_sample_without_duration() {
+        assert_eq!(super::parse_cold_sample(""), None);
+        assert_eq!(super::parse_cold_sample("thread 'main' panicked\n"), None);
+    }
+}
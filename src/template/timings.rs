@@ -12,6 +12,17 @@ pub struct Timing {
     pub part_1: Option<String>,
     pub part_2: Option<String>,
     pub total_nanos: f64,
+    /// Times with every sample run in a fresh process, if benched with `--cold`.
+    pub cold: Option<ColdTiming>,
+}
+
+/// Represents benchmark times for a single day where no sample could reuse state, such as
+/// memoization caches, left behind by an earlier one.
+#[derive(Clone, Debug, Default)]
+pub struct ColdTiming {
+    pub part_1: Option<String>,
+    pub part_2: Option<String>,
+    pub total_nanos: f64,
 }
 
 /// Represents benchmark times for a set of days.
@@ -51,7 +62,7 @@ impl Timings {
             }
         }
 
-        data.sort_unstable_by(|a, b| a.day.cmp(&b.day));
+        data.sort_unstable_by_key(|a| a.day);
         Timings { data }
     }
 
@@ -60,6 +71,16 @@ impl Timings {
         self.data.iter().map(|x| x.total_nanos).sum::<f64>() / 1_000_000_f64
     }
 
+    /// Sum up total duration of cold timings as millis. Days without cold timings are skipped.
+    pub fn total_cold_millis(&self) -> f64 {
+        self.data
+            .iter()
+            .filter_map(|x| x.cold.as_ref())
+            .map(|x| x.total_nanos)
+            .sum::<f64>()
+            / 1_000_000_f64
+    }
+
     pub fn is_day_complete(&self, day: Day) -> bool {
         self.data
             .iter()
@@ -133,6 +154,34 @@ impl From<&Timing> for JsonValue {
             },
         );
 
+        map.insert(
+            "cold".into(),
+            match &value.cold {
+                Some(x) => JsonValue::from(x),
+                None => JsonValue::Null,
+            },
+        );
+
+        JsonValue::Object(map)
+    }
+}
+
+impl From<&ColdTiming> for JsonValue {
+    fn from(value: &ColdTiming) -> Self {
+        let mut map: HashMap<String, JsonValue> = HashMap::new();
+
+        map.insert("total_nanos".into(), JsonValue::Number(value.total_nanos));
+
+        for (key, part) in [("part_1", &value.part_1), ("part_2", &value.part_2)] {
+            map.insert(
+                key.into(),
+                match part {
+                    Some(x) => JsonValue::String(x.clone()),
+                    None => JsonValue::Null,
+                },
+            );
+        }
+
         JsonValue::Object(map)
     }
 }
@@ -166,11 +215,49 @@ impl TryFrom<&JsonValue> for Timing {
             .and_then(|v| v.get::<f64>().copied())
             .ok_or("Expected timing.total_nanos to be a number.")?;
 
+        // Timings stored before cold benching existed don't have the key
+        let cold = match json.get("cold") {
+            Some(v) if !v.is_null() => Some(ColdTiming::try_from(v)?),
+            _ => None,
+        };
+
         Ok(Timing {
             day,
             part_1: part_1.cloned(),
             part_2: part_2.cloned(),
             total_nanos,
+            cold,
+        })
+    }
+}
+
+impl TryFrom<&JsonValue> for ColdTiming {
+    type Error = String;
+
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        let json = value
+            .get::<HashMap<String, JsonValue>>()
+            .ok_or("Expected timing.cold to be a JSON object.")?;
+
+        let part_1 = json
+            .get("part_1")
+            .map(|v| if v.is_null() { None } else { v.get::<String>() })
+            .ok_or("Expected timing.cold.part_1 to be null or string.")?;
+
+        let part_2 = json
+            .get("part_2")
+            .map(|v| if v.is_null() { None } else { v.get::<String>() })
+            .ok_or("Expected timing.cold.part_2 to be null or string.")?;
+
+        let total_nanos = json
+            .get("total_nanos")
+            .and_then(|v| v.get::<f64>().copied())
+            .ok_or("Expected timing.cold.total_nanos to be a number.")?;
+
+        Ok(ColdTiming {
+            part_1: part_1.cloned(),
+            part_2: part_2.cloned(),
+            total_nanos,
         })
     }
 }
@@ -191,18 +278,21 @@ mod tests {
                     part_1: Some("10ms".into()),
                     part_2: Some("20ms".into()),
                     total_nanos: 3e+10,
+                    cold: None,
                 },
                 Timing {
                     day: day!(2),
                     part_1: Some("30ms".into()),
                     part_2: Some("40ms".into()),
                     total_nanos: 7e+10,
+                    cold: None,
                 },
                 Timing {
                     day: day!(4),
                     part_1: Some("40ms".into()),
                     part_2: None,
                     total_nanos: 4e+10,
+                    cold: None,
                 },
             ],
         }
@@ -223,6 +313,24 @@ mod tests {
             assert_eq!(timing.total_nanos, 1_000_000_000_f64);
         }
 
+        #[test]
+        fn handles_cold_timings() {
+            let json = r#"{ "data": [{ "day": "01", "part_1": "1ms", "part_2": null, "total_nanos": 1000000, "cold": { "part_1": "5ms", "part_2": null, "total_nanos": 5000000 } }] }"#.to_string();
+            let timings = Timings::try_from(json).unwrap();
+            let cold = timings.data[0].cold.clone().unwrap();
+            assert_eq!(cold.part_1, Some("5ms".to_string()));
+            assert_eq!(cold.part_2, None);
+            assert_eq!(timings.total_cold_millis(), 5_f64);
+        }
+
+        #[test]
+        fn handles_timings_without_cold_key() {
+            let json = r#"{ "data": [{ "day": "01", "part_1": "1ms", "part_2": null, "total_nanos": 1000000 }] }"#.to_string();
+            let timings = Timings::try_from(json).unwrap();
+            assert!(timings.data[0].cold.is_none());
+            assert_eq!(timings.total_cold_millis(), 0_f64);
+        }
+
         #[test]
         fn handles_empty_timings() {
             let json = r#"{ "data": [] }"#.to_string();
@@ -282,10 +390,11 @@ mod tests {
                     part_1: Some("1ms".into()),
                     part_2: Some("2ms".into()),
                     total_nanos: 3_000_000_000_f64,
+                    cold: None,
                 }],
             };
 
-            assert_eq!(timings.is_day_complete(&day!(1)), true);
+            assert_eq!(timings.is_day_complete(day!(1)), true);
         }
 
         #[test]
@@ -296,10 +405,11 @@ mod tests {
                     part_1: Some("1ms".into()),
                     part_2: None,
                     total_nanos: 1_000_000_000_f64,
+                    cold: None,
                 }],
             };
 
-            assert_eq!(timings.is_day_complete(&day!(1)), false);
+            assert_eq!(timings.is_day_complete(day!(1)), false);
         }
 
         #[test]
@@ -310,10 +420,11 @@ mod tests {
                     part_1: None,
                     part_2: None,
                     total_nanos: 0.0,
+                    cold: None,
                 }],
             };
 
-            assert_eq!(timings.is_day_complete(&day!(1)), false);
+            assert_eq!(timings.is_day_complete(day!(1)), false);
         }
     }
 
@@ -334,6 +445,7 @@ mod tests {
                     part_1: None,
                     part_2: None,
                     total_nanos: 0_f64,
+                    cold: None,
                 }],
             };
             let merged = timings.merge(&other);
@@ -354,6 +466,7 @@ mod tests {
                     part_1: None,
                     part_2: None,
                     total_nanos: 0_f64,
+                    cold: None,
                 }],
             };
             let merged = timings.merge(&other);